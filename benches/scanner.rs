@@ -0,0 +1,69 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+
+use lox::parser::parser::Parser;
+use lox::scanner::scanner::Scanner;
+
+// Roughly what our generated scripts look like, repeated until it is big
+const CHUNK: &str = r#"
+/// A point with a name.
+class Point {
+  init(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  /* The distance squared, good enough for comparing */
+  distance(other) {
+    var dx = this.x - other.x;
+    var dy = this.y - other.y;
+    return dx * dx + dy * dy;
+  }
+}
+
+fun label(point, index) {
+  // Strings with escapes and interpolation
+  return "point ${index}:\t(${point.x}, ${point.y})";
+}
+
+var total = 0;
+for (var i = 0; i < 1_000; i = i + 1) {
+  var p = Point(i * 2.5, 0xFF - i);
+  if (p.distance(Point(0, 0)) > 1e3 and i != 42) {
+    total = total + 1;
+  }
+  print label(p, i);
+}
+"#;
+
+fn source() -> String {
+    CHUNK.repeat(2000)
+}
+
+fn bench_scanner(c: &mut Criterion) {
+    let source = source();
+    let mut group = c.benchmark_group("scanner");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("scan_tokens", |b| {
+        b.iter(|| Scanner::new(black_box(&source)).scan_tokens().len())
+    });
+    // What the parser sees, one token at a time and nothing kept
+    group.bench_function("stream", |b| b.iter(|| Scanner::new(black_box(&source)).count()));
+    group.finish();
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let source = source();
+    let mut group = c.benchmark_group("parser");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("scan_and_parse", |b| {
+        b.iter(|| {
+            let scanner = Scanner::new(black_box(&source));
+            Parser::new(scanner).parse().ok().unwrap().len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_scanner, bench_parser);
+criterion_main!(benches);
@@ -0,0 +1,310 @@
+use core::fmt;
+use std::io::{self, Write};
+
+use serde_json::json;
+use unicode_normalization::UnicodeNormalization;
+
+use super::{error::LoxError, snippet::render_snippet};
+use crate::token::{span::Span, token_type::Token_Type};
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "Error"),
+            Severity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+// How diagnostics are written out, picked with --error-format
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+// Something worth telling the user about a piece of source
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    // The line in the header, the token's own line. A token spanning lines
+    // keeps the line it ends on there, while span.line is where it starts
+    pub line: usize,
+    pub span: Span,
+    // The lexeme the span covers, None when it doesn't cover a token
+    pub lexeme: Option<String>,
+    pub at_end: bool,
+    pub notes: Vec<String>,
+}
+
+#[allow(dead_code)]
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: &str, span: Span) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            message: message.to_string(),
+            line: span.line,
+            span,
+            lexeme: None,
+            at_end: false,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    // Same wording as LoxError's Display, " at 'lexem'" or " at end"
+    fn location(&self) -> String {
+        if self.at_end {
+            return " at end".to_string();
+        }
+        match &self.lexeme {
+            Some(lexeme) => format!(" at '{}'", lexeme),
+            None => "".to_string(),
+        }
+    }
+
+    // The header line, then the source it points at, then any notes
+    pub fn render_human(&self, source: &str) -> String {
+        let mut rendered = format!(
+            "[line {}] {}{}: {}",
+            self.line,
+            self.severity,
+            self.location(),
+            self.message
+        );
+        // At the prompt a runtime error can point into an earlier line, in
+        // which case the span no longer matches the source we were given.
+        // Identifiers are NFC normalized, so the source is too before comparing
+        let matches_source = match &self.lexeme {
+            Some(lexeme) => source
+                .get(self.span.start..self.span.end)
+                .is_some_and(|written| written.nfc().eq(lexeme.chars())),
+            None => true,
+        };
+        if matches_source {
+            if let Some(snippet) = render_snippet(source, &self.span) {
+                rendered += "\n";
+                rendered += &snippet;
+            }
+        }
+        for note in &self.notes {
+            rendered += &format!("\n  = note: {}", note);
+        }
+        rendered
+    }
+
+    // One line of JSON:
+    // {"severity": "error" | "warning", "code": "E0002", "message": "...",
+    //  "line": 1, "column": 1, "start": 0, "end": 1,
+    //  "lexeme": "..." | null, "notes": ["..."]}
+    // line and column are 1 based, start and end are byte offsets. line is
+    // the one in the human header, column is where the span starts.
+    pub fn render_json(&self) -> String {
+        json!({
+            "severity": self.severity.to_string().to_lowercase(),
+            "code": self.code,
+            "message": self.message,
+            "line": self.line,
+            "column": self.span.column,
+            "start": self.span.start,
+            "end": self.span.end,
+            "lexeme": self.lexeme,
+            "notes": self.notes,
+        })
+        .to_string()
+    }
+}
+
+impl From<&LoxError> for Diagnostic {
+    fn from(error: &LoxError) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(Severity::Error, error.code(), error.message(), error.span());
+        diagnostic.line = error.line();
+        // Tokens made up outside the scanner only know their line
+        if diagnostic.span.line == 0 {
+            diagnostic.span.line = error.line();
+        }
+        if let Some(token) = error.token() {
+            diagnostic.lexeme = Some(token.lexem.to_string());
+            diagnostic.at_end = token.token_type == Token_Type::EOF;
+        }
+        diagnostic
+    }
+}
+
+// Everything the scanner, parser, resolver and interpreter had to say about
+// one run, kept until Lox writes it out
+#[allow(dead_code)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Diagnostics {
+        Diagnostics::new()
+    }
+}
+
+#[allow(dead_code)]
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics {
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn push_errors(&mut self, errors: &[LoxError]) {
+        for error in errors {
+            self.push(Diagnostic::from(error));
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn render(&self, source: &str, format: ErrorFormat) -> String {
+        let rendered: Vec<String> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| match format {
+                ErrorFormat::Human => diagnostic.render_human(source),
+                ErrorFormat::Json => diagnostic.render_json(),
+            })
+            .collect();
+        rendered.join("\n")
+    }
+
+    // Writes everything collected so far to stderr and starts over
+    pub fn emit(&mut self, source: &str, format: ErrorFormat) {
+        if self.diagnostics.is_empty() {
+            return;
+        }
+        let _ = writeln!(io::stderr(), "{}", self.render(source, format));
+        self.diagnostics.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, Diagnostics, ErrorFormat, Severity};
+    use crate::error::error::{LoxError, RuntimeErrorKind};
+    use crate::token::{span::Span, token::Token, token_type::Token_Type};
+
+    fn token(token_type: Token_Type, lexem: &str, span: Span) -> Token<'static> {
+        Token::spanned(token_type, lexem.to_string(), "".to_string(), span.line, span)
+    }
+
+    #[test]
+    fn test_diagnostics_human() {
+        let source = "var a = 1;\nprint a + b;";
+        let b = token(Token_Type::IDENTIFIER, "b", Span::new(21, 22, 2, 11));
+        let eof = token(Token_Type::EOF, "", Span::new(23, 23, 2, 13));
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push_errors(&[
+            LoxError::runtime(RuntimeErrorKind::UndefinedVariable, &b, "Undefined variable 'b'."),
+            LoxError::parse(&eof, "Expect ';' after value."),
+        ]);
+        diagnostics.push(
+            Diagnostic::new(Severity::Warning, "W0001", "Looks odd.", Span::new(0, 3, 1, 1))
+                .with_note("first note"),
+        );
+        assert!(diagnostics.has_errors());
+        assert_eq!(
+            diagnostics.render(source, ErrorFormat::Human),
+            "[line 2] Error at 'b': Undefined variable 'b'.\n\
+             2 | print a + b;\n  |           ^\n\
+             [line 2] Error at end: Expect ';' after value.\n\
+             2 | print a + b;\n  |             ^\n\
+             [line 1] Warning: Looks odd.\n\
+             1 | var a = 1;\n  | ^~~\n  = note: first note"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_multi_line_token() {
+        // The header has the line the string ends on, the snippet the one it starts on
+        let source = "print \"a\nb\" - 1;";
+        let string = Token::spanned(Token_Type::STRING, "\"a\nb\"", "a\nb", 2, Span::new(6, 11, 1, 7));
+        let error = LoxError::runtime(RuntimeErrorKind::TypeError, &string, "Operand must be a number.");
+        assert_eq!(
+            Diagnostic::from(&error).render_human(source),
+            "[line 2] Error at '\"a\nb\"': Operand must be a number.\n\
+             1 | print \"a\n  |       ^~"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_skip_stale_snippets() {
+        // The token came from an earlier prompt line, so the span is meaningless here
+        let stale = token(Token_Type::IDENTIFIER, "counter", Span::new(4, 11, 1, 5));
+        let error = LoxError::runtime(RuntimeErrorKind::TypeError, &stale, "Oops.");
+        assert_eq!(
+            Diagnostic::from(&error).render_human("f();"),
+            "[line 1] Error at 'counter': Oops."
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_normalized_lexeme() {
+        // Written as e and a combining accent, the lexeme holds the single é
+        let source = "print cafe\u{301};";
+        let name = token(Token_Type::IDENTIFIER, "caf\u{e9}", Span::new(6, 12, 1, 7));
+        let error = LoxError::runtime(RuntimeErrorKind::UndefinedVariable, &name, "Undefined.");
+        assert_eq!(
+            Diagnostic::from(&error).render_human(source),
+            "[line 1] Error at 'caf\u{e9}': Undefined.\n\
+             1 | print cafe\u{301};\n  |       ^~~~~"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_json() {
+        let quote = token(Token_Type::STRING, "\"a\\b\"", Span::new(6, 12, 1, 7));
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push_errors(&[LoxError::parse(&quote, "Bad \"string\".")]);
+        diagnostics.push(
+            Diagnostic::new(Severity::Warning, "W0001", "Tab\there.", Span::new(0, 0, 1, 1))
+                .with_note("a")
+                .with_note("b"),
+        );
+        assert!(diagnostics.has_errors());
+        assert_eq!(
+            diagnostics.render("", ErrorFormat::Json),
+            "{\"severity\":\"error\",\"code\":\"E0002\",\"message\":\"Bad \\\"string\\\".\",\"line\":1,\"column\":7,\"start\":6,\"end\":12,\"lexeme\":\"\\\"a\\\\b\\\"\",\"notes\":[]}\n\
+             {\"severity\":\"warning\",\"code\":\"W0001\",\"message\":\"Tab\\there.\",\"line\":1,\"column\":1,\"start\":0,\"end\":0,\"lexeme\":null,\"notes\":[\"a\",\"b\"]}"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_warnings_only() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(Diagnostic::new(Severity::Warning, "W0001", "Hmm.", Span::default()));
+        assert!(!diagnostics.has_errors());
+        assert_eq!(diagnostics.diagnostics().len(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+use core::fmt;
+
+use crate::token::{span::Span, token::Token, token_type::Token_Type};
+
+// Exit codes follow the BSD sysexits convention used by the book
+pub const STATIC_ERROR_EXIT_CODE: i32 = 65;
+pub const RUNTIME_ERROR_EXIT_CODE: i32 = 70;
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum RuntimeErrorKind {
+    TypeError,
+    UndefinedVariable,
+    DivisionByZero,
+    ArityMismatch,
+    UndefinedProperty,
+    // The tree contradicts itself, e.g. a resolved depth with no scope there
+    InternalError,
+}
+
+// Every error the scanner, parser, resolver or interpreter can produce. They are handed
+// back to Lox which decides how to report them. Tokens are boxed so a Result
+// carrying one stays small.
+#[derive(Clone, Debug)]
+pub enum LoxError {
+    Scan {
+        span: Span,
+        message: String,
+    },
+    Parse {
+        token: Box<Token<'static>>,
+        message: String,
+    },
+    Resolve {
+        token: Box<Token<'static>>,
+        message: String,
+    },
+    Runtime {
+        kind: RuntimeErrorKind,
+        token: Box<Token<'static>>,
+        message: String,
+    },
+}
+
+#[allow(dead_code)]
+impl LoxError {
+    pub fn scan(span: Span, message: &str) -> LoxError {
+        LoxError::Scan {
+            span,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn parse(token: &Token, message: &str) -> LoxError {
+        LoxError::Parse {
+            token: Box::new(token.clone().into_owned()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn resolve(token: &Token, message: &str) -> LoxError {
+        LoxError::Resolve {
+            token: Box::new(token.clone().into_owned()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn runtime(kind: RuntimeErrorKind, token: &Token, message: &str) -> LoxError {
+        LoxError::Runtime {
+            kind,
+            token: Box::new(token.clone().into_owned()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            LoxError::Scan { message, .. } => message,
+            LoxError::Parse { message, .. } => message,
+            LoxError::Resolve { message, .. } => message,
+            LoxError::Runtime { message, .. } => message,
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        match self {
+            LoxError::Scan { span, .. } => span.line,
+            LoxError::Parse { token, .. } => token.line,
+            LoxError::Resolve { token, .. } => token.line,
+            LoxError::Runtime { token, .. } => token.line,
+        }
+    }
+
+    pub fn token(&self) -> Option<&Token<'static>> {
+        match self {
+            LoxError::Scan { .. } => None,
+            LoxError::Parse { token, .. } => Some(token.as_ref()),
+            LoxError::Resolve { token, .. } => Some(token.as_ref()),
+            LoxError::Runtime { token, .. } => Some(token.as_ref()),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            LoxError::Scan { span, .. } => *span,
+            LoxError::Parse { token, .. } => token.span,
+            LoxError::Resolve { token, .. } => token.span,
+            LoxError::Runtime { token, .. } => token.span,
+        }
+    }
+
+    pub fn runtime_kind(&self) -> Option<&RuntimeErrorKind> {
+        match self {
+            LoxError::Runtime { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
+    // Stable identifier for the kind of error, for tools reading the JSON output.
+    // E00xx are static errors, E01xx runtime ones.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LoxError::Scan { .. } => "E0001",
+            LoxError::Parse { .. } => "E0002",
+            LoxError::Resolve { .. } => "E0003",
+            LoxError::Runtime { kind, .. } => match kind {
+                RuntimeErrorKind::TypeError => "E0101",
+                RuntimeErrorKind::UndefinedVariable => "E0102",
+                RuntimeErrorKind::DivisionByZero => "E0103",
+                RuntimeErrorKind::ArityMismatch => "E0104",
+                RuntimeErrorKind::UndefinedProperty => "E0105",
+                RuntimeErrorKind::InternalError => "E0106",
+            },
+        }
+    }
+
+    pub fn is_runtime(&self) -> bool {
+        matches!(self, LoxError::Runtime { .. })
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.is_runtime() {
+            return RUNTIME_ERROR_EXIT_CODE;
+        }
+        STATIC_ERROR_EXIT_CODE
+    }
+
+    // Where in the source the error happened, " at 'lexem'" or " at end"
+    pub fn location(&self) -> String {
+        match self.token() {
+            None => "".to_string(),
+            Some(token) if token.token_type == Token_Type::EOF => " at end".to_string(),
+            Some(token) => format!(" at '{}'", token.lexem),
+        }
+    }
+}
+
+impl fmt::Display for LoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[line {}] Error{}: {}",
+            self.line(),
+            self.location(),
+            self.message()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LoxError, RuntimeErrorKind};
+    use crate::token::{span::Span, token::Token, token_type::Token_Type};
+
+    #[test]
+    fn test_error_to_string() {
+        let token = Token::new(Token_Type::PLUS, "+".to_string(), "".to_string(), 3);
+        let eof = Token::new(Token_Type::EOF, "".to_string(), "".to_string(), 7);
+        assert_eq!(
+            LoxError::scan(Span::new(4, 5, 2, 1), "Unexpected character.").to_string(),
+            "[line 2] Error: Unexpected character."
+        );
+        assert_eq!(
+            LoxError::parse(&token, "Expected expression").to_string(),
+            "[line 3] Error at '+': Expected expression"
+        );
+        assert_eq!(
+            LoxError::parse(&eof, "Expected expression").to_string(),
+            "[line 7] Error at end: Expected expression"
+        );
+    }
+
+    #[test]
+    fn test_error_exit_codes() {
+        let token = Token::new(Token_Type::SLASH, "/".to_string(), "".to_string(), 1);
+        assert_eq!(LoxError::scan(Span::default(), "").exit_code(), 65);
+        assert_eq!(LoxError::parse(&token, "").exit_code(), 65);
+        assert_eq!(LoxError::resolve(&token, "").exit_code(), 65);
+        let runtime = LoxError::runtime(
+            RuntimeErrorKind::DivisionByZero,
+            &token,
+            "Division by zero.",
+        );
+        assert_eq!(runtime.exit_code(), 70);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let token = Token::new(Token_Type::SLASH, "/".to_string(), "".to_string(), 1);
+        assert_eq!(LoxError::scan(Span::default(), "").code(), "E0001");
+        assert_eq!(LoxError::parse(&token, "").code(), "E0002");
+        assert_eq!(LoxError::resolve(&token, "").code(), "E0003");
+        assert_eq!(
+            LoxError::runtime(RuntimeErrorKind::DivisionByZero, &token, "").code(),
+            "E0103"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::token::span::Span;
+
+// Tabs are shown as this many spaces so the underline can line up with them
+const TAB_WIDTH: usize = 4;
+
+// Shows the source line a span starts on with the span underlined, like
+//
+//  3 | var a = b;
+//    |         ^
+//
+// Spans running past the end of their line are only underlined up to it.
+// Returns None if the span doesn't fit in the source, which happens for
+// tokens made up by the interpreter or scanned from an earlier prompt line.
+pub fn render_snippet(source: &str, span: &Span) -> Option<String> {
+    if span.start > span.end || span.end > source.len() {
+        return None;
+    }
+    if !source.is_char_boundary(span.start) || !source.is_char_boundary(span.end) {
+        return None;
+    }
+
+    let line_start = match source[..span.start].rfind('\n') {
+        Some(newline) => newline + 1,
+        None => 0,
+    };
+    let line_end = match source[span.start..].find('\n') {
+        Some(newline) => span.start + newline,
+        None => source.len(),
+    };
+    // With CRLF endings the '\r' is part of the line break, not the line
+    let line = source[line_start..line_end].trim_end_matches('\r');
+    let underline_end = span.end.min(line_start + line.len()).max(span.start);
+
+    let padding = display_width(&source[line_start..span.start.min(line_start + line.len())]);
+    let marked = display_width(&source[span.start..underline_end]);
+    let underline = match marked {
+        0 | 1 => "^".to_string(),
+        _ => format!("^{}", "~".repeat(marked - 1)),
+    };
+
+    let gutter = span.line.to_string().len();
+    Some(format!(
+        "{:>gutter$} | {}\n{:>gutter$} | {}{}",
+        span.line,
+        expand_tabs(line),
+        "",
+        " ".repeat(padding),
+        underline,
+        gutter = gutter
+    ))
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+// How many columns the text takes up once printed, one per char
+fn display_width(text: &str) -> usize {
+    expand_tabs(text).chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_snippet;
+    use crate::scanner::scanner::Scanner;
+    use crate::token::span::Span;
+
+    // Underlines the nth token the scanner finds in the source
+    fn compare_token_snippet(source: &str, index: usize, expected: &str) {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+        assert_eq!(render_snippet(source, &tokens[index].span).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_snippet_underlines_lexeme() {
+        compare_token_snippet("var abc = 1;", 1, "1 | var abc = 1;\n  |     ^~~");
+        compare_token_snippet("print 1 + 2;", 2, "1 | print 1 + 2;\n  |         ^");
+        compare_token_snippet(
+            "1;\n2;\n3;\n4;\n5;\n6;\n7;\n8;\n9;\nprint x;",
+            19,
+            "10 | print x;\n   |       ^",
+        );
+    }
+
+    #[test]
+    fn test_snippet_tabs() {
+        compare_token_snippet("\tprint\tfoo;", 1, "1 |     print    foo;\n  |              ^~~");
+    }
+
+    #[test]
+    fn test_snippet_crlf() {
+        compare_token_snippet("var a;\r\nprint bad;\r\n", 4, "2 | print bad;\n  |       ^~~");
+        // The end of input sits on the empty line after the last break
+        compare_token_snippet("print 1\r\n", 2, "2 | \n  | ^");
+    }
+
+    #[test]
+    fn test_snippet_utf8() {
+        compare_token_snippet("print \"héllo\" + ünïcode;", 3, "1 | print \"héllo\" + ünïcode;\n  |                 ^~~~~~~");
+        compare_token_snippet("\"日本\" x", 1, "1 | \"日本\" x\n  |      ^");
+    }
+
+    #[test]
+    fn test_snippet_multiline_span() {
+        compare_token_snippet("x = \"one\ntwo\";", 2, "1 | x = \"one\n  |     ^~~~");
+    }
+
+    #[test]
+    fn test_snippet_out_of_range() {
+        assert_eq!(render_snippet("abc", &Span::new(2, 10, 1, 3)), None);
+        // Inside a multi-byte char
+        assert_eq!(render_snippet("é", &Span::new(1, 2, 1, 1)), None);
+    }
+}
@@ -0,0 +1,28 @@
+use std::cell::Cell;
+
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Assign {
+    pub name: Token<'static>,
+    pub value: Box<dyn Expression>,
+    // How many scopes out the binding lives, set by the resolver. None is global
+    pub depth: Cell<Option<usize>>,
+}
+
+impl Expression for Assign {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Assign(self)
+    }
+}
+#[allow(dead_code)]
+impl Assign {
+    pub fn new(name: Token<'static>, value: Box<dyn Expression>) -> Assign {
+        Assign {
+            name,
+            value,
+            depth: Cell::new(None),
+        }
+    }
+}
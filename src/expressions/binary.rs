@@ -1,24 +1,21 @@
-use super::{expression::Expression, visitor::Visitor, literal::Literal};
+use super::expression::{Expression, ExpressionNode};
 use crate::token::token::Token;
 
 #[allow(dead_code)]
 pub struct Binary {
     pub left: Box<dyn Expression>,
-    pub operator: Token,
+    pub operator: Token<'static>,
     pub right: Box<dyn Expression>,
 }
 
 impl Expression for Binary {
-    fn accept_printer(&self, visitor: &dyn Visitor<String>) -> String {
-        visitor.visit_binary(self)
-    }
-    fn accept_interpreter(&self, visitor: &dyn Visitor<Result<Literal, String>>) -> Result<Literal, String> {
-        visitor.visit_binary(self)
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Binary(self)
     }
 }
 #[allow(dead_code)]
 impl Binary {
-    pub fn new(left: Box<dyn Expression>, operator: Token, right: Box<dyn Expression>) -> Binary {
+    pub fn new(left: Box<dyn Expression>, operator: Token<'static>, right: Box<dyn Expression>) -> Binary {
         Binary {
             left,
             operator,
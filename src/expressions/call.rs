@@ -0,0 +1,26 @@
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Call {
+    pub callee: Box<dyn Expression>,
+    // The closing parenthesis, used to locate runtime errors from the call
+    pub paren: Token<'static>,
+    pub arguments: Vec<Box<dyn Expression>>,
+}
+
+impl Expression for Call {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Call(self)
+    }
+}
+#[allow(dead_code)]
+impl Call {
+    pub fn new(callee: Box<dyn Expression>, paren: Token<'static>, arguments: Vec<Box<dyn Expression>>) -> Call {
+        Call {
+            callee,
+            paren,
+            arguments,
+        }
+    }
+}
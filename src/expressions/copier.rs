@@ -0,0 +1,205 @@
+use std::rc::Rc;
+
+use super::assign::Assign;
+use super::binary::Binary;
+use super::call::Call;
+use super::expression::Expression;
+use super::get::Get;
+use super::grouping::Grouping;
+use super::interpolation::Interpolation;
+use super::literal::Literal;
+use super::logical::Logical;
+use super::set::Set;
+use super::super_expression::Super;
+use super::this::This;
+use super::unary::Unary;
+use super::variable::Variable;
+use super::visitor::Visitor;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::Statement;
+use crate::statements::var::Var;
+use crate::statements::visitor::Visitor as StatementVisitor;
+use crate::statements::while_statement::While;
+
+// Builds a copy of the tree, tokens, docs and resolved depths included.
+// Function bodies stay shared with the original, the same way LoxFunctions
+// share them, and are only copied once something needs to take one apart
+pub struct Copier;
+
+impl Visitor<Box<dyn Expression>> for Copier {
+    fn visit_binary(&self, element: &Binary) -> Box<dyn Expression> {
+        Box::new(Binary {
+            left: element.left.accept(self),
+            operator: element.operator.clone(),
+            right: element.right.accept(self),
+        })
+    }
+    fn visit_literal(&self, element: &Literal) -> Box<dyn Expression> {
+        Box::new(element.clone())
+    }
+    fn visit_unary(&self, element: &Unary) -> Box<dyn Expression> {
+        Box::new(Unary {
+            operator: element.operator.clone(),
+            right: element.right.accept(self),
+        })
+    }
+    fn visit_grouping(&self, element: &Grouping) -> Box<dyn Expression> {
+        Box::new(Grouping {
+            exp: element.exp.accept(self),
+        })
+    }
+    fn visit_variable(&self, element: &Variable) -> Box<dyn Expression> {
+        Box::new(element.clone())
+    }
+    fn visit_assign(&self, element: &Assign) -> Box<dyn Expression> {
+        Box::new(Assign {
+            name: element.name.clone(),
+            value: element.value.accept(self),
+            depth: element.depth.clone(),
+        })
+    }
+    fn visit_logical(&self, element: &Logical) -> Box<dyn Expression> {
+        Box::new(Logical {
+            left: element.left.accept(self),
+            operator: element.operator.clone(),
+            right: element.right.accept(self),
+        })
+    }
+    fn visit_call(&self, element: &Call) -> Box<dyn Expression> {
+        Box::new(Call {
+            callee: element.callee.accept(self),
+            paren: element.paren.clone(),
+            arguments: element.arguments.iter().map(|argument| argument.accept(self)).collect(),
+        })
+    }
+    fn visit_get(&self, element: &Get) -> Box<dyn Expression> {
+        Box::new(Get {
+            object: element.object.accept(self),
+            name: element.name.clone(),
+        })
+    }
+    fn visit_set(&self, element: &Set) -> Box<dyn Expression> {
+        Box::new(Set {
+            object: element.object.accept(self),
+            name: element.name.clone(),
+            value: element.value.accept(self),
+        })
+    }
+    fn visit_this(&self, element: &This) -> Box<dyn Expression> {
+        Box::new(This {
+            keyword: element.keyword.clone(),
+            depth: element.depth.clone(),
+        })
+    }
+    fn visit_super(&self, element: &Super) -> Box<dyn Expression> {
+        Box::new(Super {
+            keyword: element.keyword.clone(),
+            method: element.method.clone(),
+            depth: element.depth.clone(),
+        })
+    }
+    fn visit_interpolation(&self, element: &Interpolation) -> Box<dyn Expression> {
+        Box::new(Interpolation {
+            strings: element.strings.clone(),
+            expressions: element.expressions.iter().map(|expression| expression.accept(self)).collect(),
+        })
+    }
+}
+
+impl StatementVisitor<Box<dyn Statement>> for Copier {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> Box<dyn Statement> {
+        Box::new(ExpressionStatement {
+            expression: element.expression.accept(self),
+        })
+    }
+    fn visit_print(&self, element: &Print) -> Box<dyn Statement> {
+        Box::new(Print {
+            expression: element.expression.accept(self),
+        })
+    }
+    fn visit_block(&self, element: &Block) -> Box<dyn Statement> {
+        Box::new(Block {
+            statements: self.copy_statements(&element.statements),
+        })
+    }
+    fn visit_var(&self, element: &Var) -> Box<dyn Statement> {
+        Box::new(Var {
+            name: element.name.clone(),
+            initializer: element.initializer.as_ref().map(|initializer| initializer.accept(self)),
+            doc: element.doc.clone(),
+        })
+    }
+    fn visit_if(&self, element: &If) -> Box<dyn Statement> {
+        Box::new(If {
+            condition: element.condition.accept(self),
+            then_branch: element.then_branch.accept(self),
+            else_branch: element.else_branch.as_ref().map(|else_branch| else_branch.accept(self)),
+        })
+    }
+    fn visit_while(&self, element: &While) -> Box<dyn Statement> {
+        Box::new(While {
+            condition: element.condition.accept(self),
+            body: element.body.accept(self),
+        })
+    }
+    fn visit_function(&self, element: &Function) -> Box<dyn Statement> {
+        Box::new(self.copy_function(element))
+    }
+    fn visit_return(&self, element: &Return) -> Box<dyn Statement> {
+        Box::new(Return {
+            keyword: element.keyword.clone(),
+            value: element.value.as_ref().map(|value| value.accept(self)),
+        })
+    }
+    fn visit_class(&self, element: &Class) -> Box<dyn Statement> {
+        Box::new(Class {
+            name: element.name.clone(),
+            superclass: element.superclass.clone(),
+            methods: element.methods.iter().map(|method| self.copy_function(method)).collect(),
+            doc: element.doc.clone(),
+        })
+    }
+}
+
+impl Copier {
+    pub fn copy_statements(&self, statements: &[Box<dyn Statement>]) -> Vec<Box<dyn Statement>> {
+        statements.iter().map(|statement| statement.accept(self)).collect()
+    }
+
+    fn copy_function(&self, element: &Function) -> Function {
+        Function {
+            name: element.name.clone(),
+            params: element.params.clone(),
+            body: Rc::clone(&element.body),
+            doc: element.doc.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Copier;
+    use crate::expressions::json::JsonWriter;
+    use crate::parser::parser::Parser;
+    use crate::resolver::resolver::Resolver;
+    use crate::scanner::scanner::Scanner;
+
+    #[test]
+    fn test_copier_program() {
+        let code = "/// A point
+            class P < Base { init(x) { this.x = x; } sum() { return super.sum() + this.x; } }
+            fun f(n) { if (n < 1 and !false) return nil; else { while (n > 0) n = n - 1; } return n; }
+            var p = P(1); print f(p.sum()) + \"got ${p.x}\"; p.x = 2; { var y; y = -1; }";
+        let statements = Parser::new(Scanner::new(code)).parse().ok().unwrap();
+        Resolver::new().resolve(&statements).ok().unwrap();
+        let copy = Copier.copy_statements(&statements);
+        // The JSON has every token, doc and depth
+        assert_eq!(JsonWriter {}.convert_program(&copy), JsonWriter {}.convert_program(&statements));
+    }
+}
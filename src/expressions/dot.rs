@@ -0,0 +1,321 @@
+use std::cell::{Cell, RefCell};
+
+use super::assign::Assign;
+use super::binary::Binary;
+use super::call::Call;
+use super::expression::Expression;
+use super::get::Get;
+use super::grouping::Grouping;
+use super::interpolation::Interpolation;
+use super::literal::Literal;
+use super::logical::Logical;
+use super::set::Set;
+use super::super_expression::Super;
+use super::this::This;
+use super::unary::Unary;
+use super::variable::Variable;
+use super::visitor::Visitor;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::Statement;
+use crate::statements::var::Var;
+use crate::statements::visitor::Visitor as StatementVisitor;
+use crate::statements::while_statement::While;
+use crate::value::value::Value;
+
+// Draws the tree as a Graphviz graph, e.g. lox --print-ast=dot file.lox | dot -Tsvg.
+// Every visit adds its node and the edges to its children, then hands back
+// the node's id for the parent to point at. Statements are boxes,
+// expressions ellipses, and edges say which part of the parent a child is.
+pub struct DotWriter {
+    lines: RefCell<Vec<String>>,
+    nodes: Cell<usize>,
+}
+
+impl Visitor<usize> for DotWriter {
+    fn visit_binary(&self, element: &Binary) -> usize {
+        let id = self.expression_node(&element.operator.lexem);
+        self.edge(id, element.left.accept(self), "left");
+        self.edge(id, element.right.accept(self), "right");
+        id
+    }
+    fn visit_literal(&self, element: &Literal) -> usize {
+        match &element.value {
+            Value::String(string) => self.expression_node(&format!("\"{}\"", string)),
+            value => self.expression_node(&value.to_string()),
+        }
+    }
+    fn visit_unary(&self, element: &Unary) -> usize {
+        let id = self.expression_node(&element.operator.lexem);
+        self.edge(id, element.right.accept(self), "operand");
+        id
+    }
+    fn visit_grouping(&self, element: &Grouping) -> usize {
+        let id = self.expression_node("( )");
+        self.edge(id, element.exp.accept(self), "expression");
+        id
+    }
+    fn visit_variable(&self, element: &Variable) -> usize {
+        self.expression_node(&element.name.lexem)
+    }
+    fn visit_assign(&self, element: &Assign) -> usize {
+        let id = self.expression_node(&format!("{} =", element.name.lexem));
+        self.edge(id, element.value.accept(self), "value");
+        id
+    }
+    fn visit_logical(&self, element: &Logical) -> usize {
+        let id = self.expression_node(&element.operator.lexem);
+        self.edge(id, element.left.accept(self), "left");
+        self.edge(id, element.right.accept(self), "right");
+        id
+    }
+    fn visit_call(&self, element: &Call) -> usize {
+        let id = self.expression_node("call");
+        self.edge(id, element.callee.accept(self), "callee");
+        for (index, argument) in element.arguments.iter().enumerate() {
+            self.edge(id, argument.accept(self), &format!("argument {}", index));
+        }
+        id
+    }
+    fn visit_get(&self, element: &Get) -> usize {
+        let id = self.expression_node(&format!(".{}", element.name.lexem));
+        self.edge(id, element.object.accept(self), "object");
+        id
+    }
+    fn visit_set(&self, element: &Set) -> usize {
+        let id = self.expression_node(&format!(".{} =", element.name.lexem));
+        self.edge(id, element.object.accept(self), "object");
+        self.edge(id, element.value.accept(self), "value");
+        id
+    }
+    fn visit_this(&self, _element: &This) -> usize {
+        self.expression_node("this")
+    }
+    fn visit_super(&self, element: &Super) -> usize {
+        self.expression_node(&format!("super.{}", element.method.lexem))
+    }
+    // The label is the string with ${} where each expression goes
+    fn visit_interpolation(&self, element: &Interpolation) -> usize {
+        let id = self.expression_node(&format!("\"{}\"", element.strings.join("${}")));
+        for (index, expression) in element.expressions.iter().enumerate() {
+            self.edge(id, expression.accept(self), &format!("${{}} {}", index));
+        }
+        id
+    }
+}
+
+impl StatementVisitor<usize> for DotWriter {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> usize {
+        let id = self.statement_node(";");
+        self.edge(id, element.expression.accept(self), "expression");
+        id
+    }
+    fn visit_print(&self, element: &Print) -> usize {
+        let id = self.statement_node("print");
+        self.edge(id, element.expression.accept(self), "expression");
+        id
+    }
+    fn visit_block(&self, element: &Block) -> usize {
+        let id = self.statement_node("block");
+        self.statements(id, &element.statements);
+        id
+    }
+    fn visit_var(&self, element: &Var) -> usize {
+        let id = self.statement_node(&format!("var {}", element.name.lexem));
+        if let Some(initializer) = &element.initializer {
+            self.edge(id, initializer.accept(self), "initializer");
+        }
+        id
+    }
+    fn visit_if(&self, element: &If) -> usize {
+        let id = self.statement_node("if");
+        self.edge(id, element.condition.accept(self), "condition");
+        self.edge(id, element.then_branch.accept(self), "then");
+        if let Some(else_branch) = &element.else_branch {
+            self.edge(id, else_branch.accept(self), "else");
+        }
+        id
+    }
+    fn visit_while(&self, element: &While) -> usize {
+        let id = self.statement_node("while");
+        self.edge(id, element.condition.accept(self), "condition");
+        self.edge(id, element.body.accept(self), "body");
+        id
+    }
+    fn visit_function(&self, element: &Function) -> usize {
+        let params: Vec<String> = element.params.iter().map(|param| param.lexem.to_string()).collect();
+        let id = self.statement_node(&format!("fun {}({})", element.name.lexem, params.join(", ")));
+        self.statements(id, &element.body);
+        id
+    }
+    fn visit_return(&self, element: &Return) -> usize {
+        let id = self.statement_node("return");
+        if let Some(value) = &element.value {
+            self.edge(id, value.accept(self), "value");
+        }
+        id
+    }
+    fn visit_class(&self, element: &Class) -> usize {
+        let label = match &element.superclass {
+            Some(superclass) => format!("class {} < {}", element.name.lexem, superclass.name.lexem),
+            None => format!("class {}", element.name.lexem),
+        };
+        let id = self.statement_node(&label);
+        for method in &element.methods {
+            self.edge(id, self.visit_function(method), "method");
+        }
+        id
+    }
+}
+
+impl Default for DotWriter {
+    fn default() -> DotWriter {
+        DotWriter::new()
+    }
+}
+
+impl DotWriter {
+    pub fn new() -> DotWriter {
+        DotWriter {
+            lines: RefCell::new(Vec::new()),
+            nodes: Cell::new(0),
+        }
+    }
+
+    pub fn convert(&self, exp: &dyn Expression) -> String {
+        exp.accept(self);
+        self.graph()
+    }
+
+    // The statements hang off a program node, so there is one tree to draw
+    pub fn convert_program(&self, statements: &[Box<dyn Statement>]) -> String {
+        let id = self.statement_node("program");
+        self.statements(id, statements);
+        self.graph()
+    }
+
+    // Also gets the writer ready for the next tree
+    fn graph(&self) -> String {
+        self.nodes.set(0);
+        let mut graph = "digraph ast {\n    ordering=out;\n    node [fontname=\"monospace\"];\n".to_string();
+        for line in self.lines.borrow_mut().drain(..) {
+            graph += &format!("    {}\n", line);
+        }
+        graph + "}"
+    }
+
+    fn statements(&self, id: usize, statements: &[Box<dyn Statement>]) {
+        for statement in statements {
+            self.edge(id, statement.accept(self), "");
+        }
+    }
+
+    fn statement_node(&self, label: &str) -> usize {
+        self.node(label, "box")
+    }
+
+    fn expression_node(&self, label: &str) -> usize {
+        self.node(label, "ellipse")
+    }
+
+    // Ids are handed out in the order nodes are drawn, from 0
+    fn node(&self, label: &str, shape: &str) -> usize {
+        let id = self.nodes.get();
+        self.nodes.set(id + 1);
+        let line = format!("n{} [label={}, shape={}];", id, quote(label), shape);
+        self.lines.borrow_mut().push(line);
+        id
+    }
+
+    fn edge(&self, from: usize, to: usize, label: &str) {
+        let line = match label.is_empty() {
+            true => format!("n{} -> n{};", from, to),
+            false => format!("n{} -> n{} [label={}];", from, to, quote(label)),
+        };
+        self.lines.borrow_mut().push(line);
+    }
+}
+
+// A DOT string, escaped so any lexeme or string literal is safe in a label
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DotWriter;
+    use crate::parser::parser::Parser;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::statement::Statement;
+
+    fn parse(code: &str) -> Vec<Box<dyn Statement>> {
+        Parser::new(Scanner::new(code)).parse().ok().unwrap()
+    }
+
+    #[test]
+    fn test_dot_expression() {
+        let writer = DotWriter::new();
+        let graph = writer.convert_program(&parse("-1 * (a + \"q\\\"\");"));
+        let expected = r#"digraph ast {
+    ordering=out;
+    node [fontname="monospace"];
+    n0 [label="program", shape=box];
+    n1 [label=";", shape=box];
+    n2 [label="*", shape=ellipse];
+    n3 [label="-", shape=ellipse];
+    n4 [label="1", shape=ellipse];
+    n3 -> n4 [label="operand"];
+    n2 -> n3 [label="left"];
+    n5 [label="( )", shape=ellipse];
+    n6 [label="+", shape=ellipse];
+    n7 [label="a", shape=ellipse];
+    n6 -> n7 [label="left"];
+    n8 [label="\"q\"\"", shape=ellipse];
+    n6 -> n8 [label="right"];
+    n5 -> n6 [label="expression"];
+    n2 -> n5 [label="right"];
+    n1 -> n2 [label="expression"];
+    n0 -> n1;
+}"#;
+        assert_eq!(graph, expected);
+        // The writer starts over for the next tree
+        assert_eq!(writer.convert_program(&parse("-1 * (a + \"q\\\"\");")), expected);
+    }
+
+    #[test]
+    fn test_dot_statements() {
+        let code = "class A < B { m(x) { return x; } } if (a) print \"${a}!\"; else f(1, 2);";
+        let graph = DotWriter::new().convert_program(&parse(code));
+        for line in [
+            "n1 [label=\"class A < B\", shape=box];",
+            "n2 [label=\"fun m(x)\", shape=box];",
+            "n3 -> n4 [label=\"value\"];",
+            "n1 -> n2 [label=\"method\"];",
+            "n5 [label=\"if\", shape=box];",
+            "n8 [label=\"\\\"${}!\\\"\", shape=ellipse];",
+            "n8 -> n9 [label=\"${} 0\"];",
+            "n11 -> n12 [label=\"callee\"];",
+            "n11 -> n14 [label=\"argument 1\"];",
+            "n5 -> n7 [label=\"then\"];",
+            "n5 -> n10 [label=\"else\"];",
+        ] {
+            assert!(graph.contains(line), "{} not in\n{}", line, graph);
+        }
+    }
+}
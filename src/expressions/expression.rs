@@ -1,6 +1,163 @@
-use super::{literal::Literal, visitor::Visitor};
+use std::any::Any;
 
-pub trait Expression {
-    fn accept_printer(&self, visitor: &dyn Visitor<String>) -> String;
-    fn accept_interpreter(&self, visitor: &dyn Visitor<Result<Literal, String>>) -> Result<Literal, String>;
+use self::sealed::Sealed;
+use super::visitor::Visitor;
+use super::{
+    assign::Assign, binary::Binary, call::Call, get::Get, grouping::Grouping, interpolation::Interpolation, literal::Literal,
+    logical::Logical, set::Set, super_expression::Super, this::This, unary::Unary, variable::Variable,
+};
+
+// A node only says which kind it is, so adding a pass over the tree never
+// means touching the nodes: implement Visitor<R> for any R, or match on node()
+pub trait Expression: IntoAny + Sealed {
+    fn node(&self) -> ExpressionNode<'_>;
+}
+
+// Every kind of expression, borrowed
+pub enum ExpressionNode<'a> {
+    Assign(&'a Assign),
+    Binary(&'a Binary),
+    Call(&'a Call),
+    Get(&'a Get),
+    Grouping(&'a Grouping),
+    Interpolation(&'a Interpolation),
+    Literal(&'a Literal),
+    Logical(&'a Logical),
+    Set(&'a Set),
+    Super(&'a Super),
+    This(&'a This),
+    Unary(&'a Unary),
+    Variable(&'a Variable),
+}
+
+impl dyn Expression + '_ {
+    pub fn accept<R>(&self, visitor: &dyn Visitor<R>) -> R {
+        match self.node() {
+            ExpressionNode::Assign(element) => visitor.visit_assign(element),
+            ExpressionNode::Binary(element) => visitor.visit_binary(element),
+            ExpressionNode::Call(element) => visitor.visit_call(element),
+            ExpressionNode::Get(element) => visitor.visit_get(element),
+            ExpressionNode::Grouping(element) => visitor.visit_grouping(element),
+            ExpressionNode::Interpolation(element) => visitor.visit_interpolation(element),
+            ExpressionNode::Literal(element) => visitor.visit_literal(element),
+            ExpressionNode::Logical(element) => visitor.visit_logical(element),
+            ExpressionNode::Set(element) => visitor.visit_set(element),
+            ExpressionNode::Super(element) => visitor.visit_super(element),
+            ExpressionNode::This(element) => visitor.visit_this(element),
+            ExpressionNode::Unary(element) => visitor.visit_unary(element),
+            ExpressionNode::Variable(element) => visitor.visit_variable(element),
+        }
+    }
+}
+
+// Every kind of expression, owned, for passes that take the tree apart
+pub enum OwnedExpression {
+    Assign(Box<Assign>),
+    Binary(Box<Binary>),
+    Call(Box<Call>),
+    Get(Box<Get>),
+    Grouping(Box<Grouping>),
+    Interpolation(Box<Interpolation>),
+    Literal(Box<Literal>),
+    Logical(Box<Logical>),
+    Set(Box<Set>),
+    Super(Box<Super>),
+    This(Box<This>),
+    Unary(Box<Unary>),
+    Variable(Box<Variable>),
+}
+
+impl dyn Expression {
+    pub fn into_node(self: Box<Self>) -> OwnedExpression {
+        match self.node() {
+            ExpressionNode::Assign(_) => OwnedExpression::Assign(downcast(self)),
+            ExpressionNode::Binary(_) => OwnedExpression::Binary(downcast(self)),
+            ExpressionNode::Call(_) => OwnedExpression::Call(downcast(self)),
+            ExpressionNode::Get(_) => OwnedExpression::Get(downcast(self)),
+            ExpressionNode::Grouping(_) => OwnedExpression::Grouping(downcast(self)),
+            ExpressionNode::Interpolation(_) => OwnedExpression::Interpolation(downcast(self)),
+            ExpressionNode::Literal(_) => OwnedExpression::Literal(downcast(self)),
+            ExpressionNode::Logical(_) => OwnedExpression::Logical(downcast(self)),
+            ExpressionNode::Set(_) => OwnedExpression::Set(downcast(self)),
+            ExpressionNode::Super(_) => OwnedExpression::Super(downcast(self)),
+            ExpressionNode::This(_) => OwnedExpression::This(downcast(self)),
+            ExpressionNode::Unary(_) => OwnedExpression::Unary(downcast(self)),
+            ExpressionNode::Variable(_) => OwnedExpression::Variable(downcast(self)),
+        }
+    }
+}
+
+// Lets into_node recover the concrete node behind a Box<dyn Expression> or
+// a Box<dyn Statement>
+pub trait IntoAny {
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any> IntoAny for T {
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+// Only the nodes of this crate can be expressions or statements, so node()
+// always names the type behind the box. The trait is public but can't be
+// named from outside, which keeps other crates from implementing either one
+pub(crate) mod sealed {
+    pub trait Sealed {}
+}
+
+impl Sealed for Assign {}
+impl Sealed for Binary {}
+impl Sealed for Call {}
+impl Sealed for Get {}
+impl Sealed for Grouping {}
+impl Sealed for Interpolation {}
+impl Sealed for Literal {}
+impl Sealed for Logical {}
+impl Sealed for Set {}
+impl Sealed for Super {}
+impl Sealed for This {}
+impl Sealed for Unary {}
+impl Sealed for Variable {}
+
+// Shared by both into_node. The traits are sealed and node() says which kind
+// is behind the box, so the downcast can't miss
+pub(crate) fn downcast<T: Any, N: IntoAny + ?Sized>(node: Box<N>) -> Box<T> {
+    match node.into_any().downcast::<T>() {
+        Ok(element) => element,
+        Err(_) => panic!("node() disagrees with the type behind it"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expression, ExpressionNode};
+    use crate::parser::parser::Parser;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::statement::StatementNode;
+    use crate::value::value::Value;
+
+    // A pass written without touching any node, numbers added up at parse time
+    fn constant_sum(expression: &dyn Expression) -> f64 {
+        match expression.node() {
+            ExpressionNode::Literal(literal) => match literal.value {
+                Value::Number(number) => number,
+                _ => 0.0,
+            },
+            ExpressionNode::Binary(binary) => constant_sum(binary.left.as_ref()) + constant_sum(binary.right.as_ref()),
+            ExpressionNode::Grouping(grouping) => constant_sum(grouping.exp.as_ref()),
+            ExpressionNode::Unary(unary) => constant_sum(unary.right.as_ref()),
+            _ => 0.0,
+        }
+    }
+
+    #[test]
+    fn test_expression_node_dispatch() {
+        let statements = Parser::new(Scanner::new("1 + (2 * x) - -3;")).parse().ok().unwrap();
+        let expression = match statements[0].node() {
+            StatementNode::Expression(statement) => statement.expression.as_ref(),
+            _ => panic!("Expected an expression statement"),
+        };
+        assert_eq!(constant_sum(expression), 6.0);
+    }
 }
@@ -0,0 +1,21 @@
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+// Property access, object.name
+#[allow(dead_code)]
+pub struct Get {
+    pub object: Box<dyn Expression>,
+    pub name: Token<'static>,
+}
+
+impl Expression for Get {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Get(self)
+    }
+}
+#[allow(dead_code)]
+impl Get {
+    pub fn new(object: Box<dyn Expression>, name: Token<'static>) -> Get {
+        Get { object, name }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{expression::Expression, visitor::Visitor, literal::Literal};
+use super::expression::{Expression, ExpressionNode};
 
 #[allow(dead_code)]
 pub struct Grouping {
@@ -6,11 +6,8 @@ pub struct Grouping {
 }
 
 impl Expression for Grouping {
-    fn accept_printer(&self, visitor: &dyn super::visitor::Visitor<String>) -> String {
-        visitor.visit_grouping(self)
-    }
-    fn accept_interpreter(&self, visitor: &dyn Visitor<Result<Literal, String>>) -> Result<Literal, String> {
-        visitor.visit_grouping(self)
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Grouping(self)
     }
 }
 #[allow(dead_code)]
@@ -0,0 +1,24 @@
+use super::expression::{Expression, ExpressionNode};
+
+// "a ${b} c" keeps the pieces of string around the embedded expressions, so
+// there is always one more string than there are expressions
+#[allow(dead_code)]
+pub struct Interpolation {
+    pub strings: Vec<String>,
+    pub expressions: Vec<Box<dyn Expression>>,
+}
+
+impl Expression for Interpolation {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Interpolation(self)
+    }
+}
+#[allow(dead_code)]
+impl Interpolation {
+    pub fn new(strings: Vec<String>, expressions: Vec<Box<dyn Expression>>) -> Interpolation {
+        Interpolation {
+            strings,
+            expressions,
+        }
+    }
+}
@@ -0,0 +1,626 @@
+use std::cell::Cell;
+
+use serde_json::{json, Value as Json};
+
+use super::assign::Assign;
+use super::binary::Binary;
+use super::call::Call;
+use super::expression::Expression;
+use super::get::Get;
+use super::grouping::Grouping;
+use super::interpolation::Interpolation;
+use super::literal::Literal;
+use super::logical::Logical;
+use super::set::Set;
+use super::super_expression::Super;
+use super::this::This;
+use super::unary::Unary;
+use super::variable::Variable;
+use super::visitor::Visitor;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::Statement;
+use crate::statements::var::Var;
+use crate::statements::visitor::Visitor as StatementVisitor;
+use crate::statements::while_statement::While;
+use crate::token::{span::Span, token::Token, token_type::Token_Type};
+use crate::value::value::Value;
+
+// Bumped whenever the schema in notes/ast-json.md changes
+pub const AST_JSON_VERSION: u64 = 1;
+
+// Writes the tree as JSON following notes/ast-json.md. Nothing is left out,
+// read_program gives back the same tree, tokens, spans and resolved depths included
+pub struct JsonWriter {}
+
+impl Visitor<Json> for JsonWriter {
+    fn visit_binary(&self, element: &Binary) -> Json {
+        json!({
+            "kind": "Binary",
+            "left": element.left.accept(self),
+            "operator": write_token(&element.operator),
+            "right": element.right.accept(self),
+        })
+    }
+    fn visit_literal(&self, element: &Literal) -> Json {
+        json!({ "kind": "Literal", "value": write_value(&element.value) })
+    }
+    fn visit_unary(&self, element: &Unary) -> Json {
+        json!({
+            "kind": "Unary",
+            "operator": write_token(&element.operator),
+            "right": element.right.accept(self),
+        })
+    }
+    fn visit_grouping(&self, element: &Grouping) -> Json {
+        json!({ "kind": "Grouping", "expression": element.exp.accept(self) })
+    }
+    fn visit_variable(&self, element: &Variable) -> Json {
+        let node = json!({ "kind": "Variable", "name": write_token(&element.name) });
+        with_depth(node, &element.depth)
+    }
+    fn visit_assign(&self, element: &Assign) -> Json {
+        let node = json!({
+            "kind": "Assign",
+            "name": write_token(&element.name),
+            "value": element.value.accept(self),
+        });
+        with_depth(node, &element.depth)
+    }
+    fn visit_logical(&self, element: &Logical) -> Json {
+        json!({
+            "kind": "Logical",
+            "left": element.left.accept(self),
+            "operator": write_token(&element.operator),
+            "right": element.right.accept(self),
+        })
+    }
+    fn visit_call(&self, element: &Call) -> Json {
+        let arguments: Vec<Json> = element.arguments.iter().map(|argument| argument.accept(self)).collect();
+        json!({
+            "kind": "Call",
+            "callee": element.callee.accept(self),
+            "paren": write_token(&element.paren),
+            "arguments": arguments,
+        })
+    }
+    fn visit_get(&self, element: &Get) -> Json {
+        json!({
+            "kind": "Get",
+            "object": element.object.accept(self),
+            "name": write_token(&element.name),
+        })
+    }
+    fn visit_set(&self, element: &Set) -> Json {
+        json!({
+            "kind": "Set",
+            "object": element.object.accept(self),
+            "name": write_token(&element.name),
+            "value": element.value.accept(self),
+        })
+    }
+    fn visit_this(&self, element: &This) -> Json {
+        let node = json!({ "kind": "This", "keyword": write_token(&element.keyword) });
+        with_depth(node, &element.depth)
+    }
+    fn visit_super(&self, element: &Super) -> Json {
+        let node = json!({
+            "kind": "Super",
+            "keyword": write_token(&element.keyword),
+            "method": write_token(&element.method),
+        });
+        with_depth(node, &element.depth)
+    }
+    fn visit_interpolation(&self, element: &Interpolation) -> Json {
+        let expressions: Vec<Json> = element.expressions.iter().map(|expression| expression.accept(self)).collect();
+        json!({
+            "kind": "Interpolation",
+            "strings": element.strings,
+            "expressions": expressions,
+        })
+    }
+}
+
+impl StatementVisitor<Json> for JsonWriter {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> Json {
+        json!({ "kind": "Expression", "expression": element.expression.accept(self) })
+    }
+    fn visit_print(&self, element: &Print) -> Json {
+        json!({ "kind": "Print", "expression": element.expression.accept(self) })
+    }
+    fn visit_block(&self, element: &Block) -> Json {
+        json!({ "kind": "Block", "statements": self.statements(&element.statements) })
+    }
+    fn visit_var(&self, element: &Var) -> Json {
+        let node = json!({
+            "kind": "Var",
+            "name": write_token(&element.name),
+            "initializer": element.initializer.as_ref().map(|initializer| initializer.accept(self)),
+        });
+        with_doc(node, &element.doc)
+    }
+    fn visit_if(&self, element: &If) -> Json {
+        json!({
+            "kind": "If",
+            "condition": element.condition.accept(self),
+            "then": element.then_branch.accept(self),
+            "else": element.else_branch.as_ref().map(|else_branch| else_branch.accept(self)),
+        })
+    }
+    fn visit_while(&self, element: &While) -> Json {
+        json!({
+            "kind": "While",
+            "condition": element.condition.accept(self),
+            "body": element.body.accept(self),
+        })
+    }
+    fn visit_function(&self, element: &Function) -> Json {
+        let params: Vec<Json> = element.params.iter().map(write_token).collect();
+        let node = json!({
+            "kind": "Function",
+            "name": write_token(&element.name),
+            "params": params,
+            "body": self.statements(&element.body),
+        });
+        with_doc(node, &element.doc)
+    }
+    fn visit_return(&self, element: &Return) -> Json {
+        json!({
+            "kind": "Return",
+            "keyword": write_token(&element.keyword),
+            "value": element.value.as_ref().map(|value| value.accept(self)),
+        })
+    }
+    fn visit_class(&self, element: &Class) -> Json {
+        let methods: Vec<Json> = element.methods.iter().map(|method| self.visit_function(method)).collect();
+        let node = json!({
+            "kind": "Class",
+            "name": write_token(&element.name),
+            "superclass": element.superclass.as_ref().map(|superclass| self.visit_variable(superclass)),
+            "methods": methods,
+        });
+        with_doc(node, &element.doc)
+    }
+}
+
+#[allow(dead_code)]
+impl JsonWriter {
+    pub fn convert_program(&self, statements: &[Box<dyn Statement>]) -> String {
+        let program = json!({
+            "version": AST_JSON_VERSION,
+            "statements": self.statements(statements),
+        });
+        serde_json::to_string_pretty(&program).unwrap()
+    }
+
+    fn statements(&self, statements: &[Box<dyn Statement>]) -> Vec<Json> {
+        statements.iter().map(|statement| statement.accept(self)).collect()
+    }
+}
+
+fn write_token(token: &Token) -> Json {
+    let node = json!({
+        "type": format!("{:?}", token.token_type),
+        "lexeme": token.lexem,
+        "literal": token.literal,
+        "line": token.line,
+        "span": {
+            "start": token.span.start,
+            "end": token.span.end,
+            "line": token.span.line,
+            "column": token.span.column,
+        },
+    });
+    with_doc(node, &token.doc)
+}
+
+// Only what the parser can put in a Literal reads back, a function or an
+// instance is written down for people to look at and rejected by read_program
+fn write_value(value: &Value) -> Json {
+    match value {
+        Value::Nil => json!({ "type": "nil" }),
+        Value::Bool(boolean) => json!({ "type": "bool", "value": boolean }),
+        // JSON has no infinity or NaN, those go as strings
+        Value::Number(number) if number.is_finite() => json!({ "type": "number", "value": number }),
+        Value::Number(number) => json!({ "type": "number", "value": number.to_string() }),
+        Value::String(string) => json!({ "type": "string", "value": string.as_ref() }),
+        runtime => json!({ "type": "runtime", "value": runtime.to_string() }),
+    }
+}
+
+fn with_depth(mut node: Json, depth: &Cell<Option<usize>>) -> Json {
+    if let Some(depth) = depth.get() {
+        node["depth"] = json!(depth);
+    }
+    node
+}
+
+fn with_doc(mut node: Json, doc: &Option<String>) -> Json {
+    if let Some(doc) = doc {
+        node["doc"] = json!(doc);
+    }
+    node
+}
+
+// The other way, errors say what was expected and in which kind of node
+
+pub fn read_program(text: &str) -> Result<Vec<Box<dyn Statement>>, String> {
+    let program: Json = match serde_json::from_str(text) {
+        Ok(program) => program,
+        Err(error) => return Err(format!("Invalid JSON: {}", error)),
+    };
+    match field(&program, "version", "program")?.as_u64() {
+        Some(AST_JSON_VERSION) => {}
+        _ => return Err(format!("Unsupported AST version, expected {}", AST_JSON_VERSION)),
+    }
+    read_statements(field(&program, "statements", "program")?, "program")
+}
+
+pub fn read_statement(node: &Json) -> Result<Box<dyn Statement>, String> {
+    let kind = kind(node)?;
+    let statement: Box<dyn Statement> = match kind {
+        "Expression" => Box::new(ExpressionStatement::new(read_expression(field(node, "expression", kind)?)?)),
+        "Print" => Box::new(Print::new(read_expression(field(node, "expression", kind)?)?)),
+        "Block" => Box::new(Block::new(read_statements(field(node, "statements", kind)?, kind)?)),
+        "Var" => Box::new(Var::new(
+            read_token(field(node, "name", kind)?)?,
+            optional(node, "initializer", read_expression)?,
+            read_doc(node, kind)?,
+        )),
+        "If" => Box::new(If::new(
+            read_expression(field(node, "condition", kind)?)?,
+            read_statement(field(node, "then", kind)?)?,
+            optional(node, "else", read_statement)?,
+        )),
+        "While" => Box::new(While::new(
+            read_expression(field(node, "condition", kind)?)?,
+            read_statement(field(node, "body", kind)?)?,
+        )),
+        "Function" => Box::new(read_function(node)?),
+        "Return" => Box::new(Return::new(
+            read_token(field(node, "keyword", kind)?)?,
+            optional(node, "value", read_expression)?,
+        )),
+        "Class" => {
+            let mut methods = Vec::new();
+            for method in array(field(node, "methods", kind)?, "methods", kind)? {
+                methods.push(read_function(method)?);
+            }
+            Box::new(Class::new(
+                read_token(field(node, "name", kind)?)?,
+                optional(node, "superclass", read_variable)?,
+                methods,
+                read_doc(node, kind)?,
+            ))
+        }
+        _ => return Err(format!("Unknown statement kind '{}'", kind)),
+    };
+    Ok(statement)
+}
+
+pub fn read_expression(node: &Json) -> Result<Box<dyn Expression>, String> {
+    let kind = kind(node)?;
+    let expression: Box<dyn Expression> = match kind {
+        "Binary" => Box::new(Binary::new(
+            read_expression(field(node, "left", kind)?)?,
+            read_token(field(node, "operator", kind)?)?,
+            read_expression(field(node, "right", kind)?)?,
+        )),
+        "Literal" => Box::new(Literal::new(read_value(field(node, "value", kind)?)?)),
+        "Unary" => Box::new(Unary::new(
+            read_token(field(node, "operator", kind)?)?,
+            read_expression(field(node, "right", kind)?)?,
+        )),
+        "Grouping" => Box::new(Grouping::new(read_expression(field(node, "expression", kind)?)?)),
+        "Variable" => Box::new(read_variable(node)?),
+        "Assign" => {
+            let assign = Assign::new(
+                read_token(field(node, "name", kind)?)?,
+                read_expression(field(node, "value", kind)?)?,
+            );
+            assign.depth.set(read_depth(node, kind)?);
+            Box::new(assign)
+        }
+        "Logical" => Box::new(Logical::new(
+            read_expression(field(node, "left", kind)?)?,
+            read_token(field(node, "operator", kind)?)?,
+            read_expression(field(node, "right", kind)?)?,
+        )),
+        "Call" => {
+            let mut arguments = Vec::new();
+            for argument in array(field(node, "arguments", kind)?, "arguments", kind)? {
+                arguments.push(read_expression(argument)?);
+            }
+            Box::new(Call::new(
+                read_expression(field(node, "callee", kind)?)?,
+                read_token(field(node, "paren", kind)?)?,
+                arguments,
+            ))
+        }
+        "Get" => Box::new(Get::new(
+            read_expression(field(node, "object", kind)?)?,
+            read_token(field(node, "name", kind)?)?,
+        )),
+        "Set" => Box::new(Set::new(
+            read_expression(field(node, "object", kind)?)?,
+            read_token(field(node, "name", kind)?)?,
+            read_expression(field(node, "value", kind)?)?,
+        )),
+        "This" => {
+            let this = This::new(read_token(field(node, "keyword", kind)?)?);
+            this.depth.set(read_depth(node, kind)?);
+            Box::new(this)
+        }
+        "Super" => {
+            let super_expression = Super::new(
+                read_token(field(node, "keyword", kind)?)?,
+                read_token(field(node, "method", kind)?)?,
+            );
+            super_expression.depth.set(read_depth(node, kind)?);
+            Box::new(super_expression)
+        }
+        "Interpolation" => {
+            let mut strings = Vec::new();
+            for string in array(field(node, "strings", kind)?, "strings", kind)? {
+                strings.push(string_of(string, "strings", kind)?.to_string());
+            }
+            let mut expressions = Vec::new();
+            for expression in array(field(node, "expressions", kind)?, "expressions", kind)? {
+                expressions.push(read_expression(expression)?);
+            }
+            // The pieces alternate, starting and ending with a string
+            if strings.len() != expressions.len() + 1 {
+                return Err("Interpolation needs one more string than expressions".to_string());
+            }
+            Box::new(Interpolation::new(strings, expressions))
+        }
+        _ => return Err(format!("Unknown expression kind '{}'", kind)),
+    };
+    Ok(expression)
+}
+
+fn read_statements(node: &Json, kind: &str) -> Result<Vec<Box<dyn Statement>>, String> {
+    let mut statements = Vec::new();
+    for statement in array(node, "statements", kind)? {
+        statements.push(read_statement(statement)?);
+    }
+    Ok(statements)
+}
+
+fn read_function(node: &Json) -> Result<Function, String> {
+    let kind = expect_kind(node, "Function")?;
+    let mut params = Vec::new();
+    for param in array(field(node, "params", kind)?, "params", kind)? {
+        params.push(read_token(param)?);
+    }
+    Ok(Function::new(
+        read_token(field(node, "name", kind)?)?,
+        params,
+        read_statements(field(node, "body", kind)?, kind)?,
+        read_doc(node, kind)?,
+    ))
+}
+
+fn read_variable(node: &Json) -> Result<Variable, String> {
+    let kind = expect_kind(node, "Variable")?;
+    let variable = Variable::new(read_token(field(node, "name", kind)?)?);
+    variable.depth.set(read_depth(node, kind)?);
+    Ok(variable)
+}
+
+fn read_token(node: &Json) -> Result<Token<'static>, String> {
+    let name = string_of(field(node, "type", "token")?, "type", "token")?;
+    let token_type = match Token_Type::from_name(name) {
+        Some(token_type) => token_type,
+        None => return Err(format!("Unknown token type '{}'", name)),
+    };
+    let span = field(node, "span", "token")?;
+    let mut token = Token::spanned(
+        token_type,
+        string_of(field(node, "lexeme", "token")?, "lexeme", "token")?.to_string(),
+        string_of(field(node, "literal", "token")?, "literal", "token")?.to_string(),
+        number_of(field(node, "line", "token")?, "line", "token")?,
+        Span::new(
+            number_of(field(span, "start", "span")?, "start", "span")?,
+            number_of(field(span, "end", "span")?, "end", "span")?,
+            number_of(field(span, "line", "span")?, "line", "span")?,
+            number_of(field(span, "column", "span")?, "column", "span")?,
+        ),
+    );
+    token.doc = read_doc(node, "token")?;
+    Ok(token)
+}
+
+fn read_value(node: &Json) -> Result<Value, String> {
+    let value_type = string_of(field(node, "type", "value")?, "type", "value")?;
+    let value = match value_type {
+        "nil" => Value::Nil,
+        "bool" => match field(node, "value", "value")?.as_bool() {
+            Some(boolean) => Value::Bool(boolean),
+            None => return Err("Expected a boolean in 'value' of value".to_string()),
+        },
+        "number" => {
+            let number = field(node, "value", "value")?;
+            let parsed = match number {
+                Json::String(text) => text.parse::<f64>().ok().filter(|number| !number.is_finite()),
+                _ => number.as_f64(),
+            };
+            match parsed {
+                Some(number) => Value::Number(number),
+                None => return Err("Expected a number in 'value' of value".to_string()),
+            }
+        }
+        "string" => Value::string(string_of(field(node, "value", "value")?, "value", "value")?),
+        "runtime" => return Err("Runtime values can't be read back into the tree".to_string()),
+        _ => return Err(format!("Unknown value type '{}'", value_type)),
+    };
+    Ok(value)
+}
+
+fn read_depth(node: &Json, kind: &str) -> Result<Option<usize>, String> {
+    match node.get("depth") {
+        None | Some(Json::Null) => Ok(None),
+        Some(depth) => Ok(Some(number_of(depth, "depth", kind)?)),
+    }
+}
+
+fn read_doc(node: &Json, kind: &str) -> Result<Option<String>, String> {
+    match node.get("doc") {
+        None | Some(Json::Null) => Ok(None),
+        Some(doc) => Ok(Some(string_of(doc, "doc", kind)?.to_string())),
+    }
+}
+
+// Missing and null both mean there is nothing there
+fn optional<T>(node: &Json, name: &str, read: fn(&Json) -> Result<T, String>) -> Result<Option<T>, String> {
+    match node.get(name) {
+        None | Some(Json::Null) => Ok(None),
+        Some(value) => Ok(Some(read(value)?)),
+    }
+}
+
+fn kind(node: &Json) -> Result<&str, String> {
+    match node.get("kind").and_then(Json::as_str) {
+        Some(kind) => Ok(kind),
+        None => Err("Expected an object with a 'kind'".to_string()),
+    }
+}
+
+fn expect_kind<'a>(node: &Json, expected: &'a str) -> Result<&'a str, String> {
+    let kind = kind(node)?;
+    if kind != expected {
+        return Err(format!("Expected a {} node, found {}", expected, kind));
+    }
+    Ok(expected)
+}
+
+fn field<'a>(node: &'a Json, name: &str, kind: &str) -> Result<&'a Json, String> {
+    match node.get(name) {
+        Some(value) => Ok(value),
+        None => Err(format!("Missing field '{}' in {}", name, kind)),
+    }
+}
+
+fn array<'a>(node: &'a Json, name: &str, kind: &str) -> Result<&'a Vec<Json>, String> {
+    match node.as_array() {
+        Some(array) => Ok(array),
+        None => Err(format!("Expected an array in '{}' of {}", name, kind)),
+    }
+}
+
+fn string_of<'a>(node: &'a Json, name: &str, kind: &str) -> Result<&'a str, String> {
+    match node.as_str() {
+        Some(string) => Ok(string),
+        None => Err(format!("Expected a string in '{}' of {}", name, kind)),
+    }
+}
+
+fn number_of(node: &Json, name: &str, kind: &str) -> Result<usize, String> {
+    match node.as_u64() {
+        Some(number) => Ok(number as usize),
+        None => Err(format!("Expected a whole number in '{}' of {}", name, kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{read_expression, read_program, JsonWriter};
+    use crate::error::error::RuntimeErrorKind;
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::expressions::printer::Printer;
+    use crate::parser::parser::Parser;
+    use crate::resolver::resolver::Resolver;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::statement::Statement;
+    use serde_json::json;
+
+    fn parse(code: &str) -> Vec<Box<dyn Statement>> {
+        Parser::new(Scanner::new(code)).parse().ok().unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let code = "/// A point
+            class P < Base { init(x) { this.x = x; } sum() { return super.sum() + this.x * -1.5e3; } }
+            fun f(n) { if (n < 1 and !false) return nil; else { while (n > 0) n = n - 1; } return n; }
+            /// The answer
+            var p = P(1); print f(p.sum()) + \"got ${p.x} and ${\"\\n\"}\"; p.x = 2; { var y; }";
+        let statements = parse(code);
+        Resolver::new().resolve(&statements).ok().unwrap();
+        let written = JsonWriter {}.convert_program(&statements);
+        let read = read_program(&written).unwrap();
+        assert_eq!(JsonWriter {}.convert_program(&read), written);
+        assert_eq!(Printer {}.convert_program(&read), Printer {}.convert_program(&statements));
+        assert_eq!(read[2].doc(), Some("The answer"));
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let statements = parse("-2;");
+        let written: serde_json::Value = serde_json::from_str(&JsonWriter {}.convert_program(&statements)).unwrap();
+        assert_eq!(
+            written,
+            json!({
+                "version": 1,
+                "statements": [{
+                    "kind": "Expression",
+                    "expression": {
+                        "kind": "Unary",
+                        "operator": {
+                            "type": "MINUS", "lexeme": "-", "literal": "", "line": 1,
+                            "span": { "start": 0, "end": 1, "line": 1, "column": 1 }
+                        },
+                        "right": { "kind": "Literal", "value": { "type": "number", "value": 2.0 } }
+                    }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        assert_eq!(
+            read_expression(&json!({ "kind": "Binary", "left": { "kind": "Literal", "value": { "type": "nil" } } })).err(),
+            Some("Missing field 'operator' in Binary".to_string())
+        );
+        assert_eq!(
+            read_expression(&json!({ "kind": "Lambda" })).err(),
+            Some("Unknown expression kind 'Lambda'".to_string())
+        );
+        assert_eq!(
+            read_program("{\"version\": 2, \"statements\": []}").err(),
+            Some("Unsupported AST version, expected 1".to_string())
+        );
+        assert!(read_program("[").err().unwrap().starts_with("Invalid JSON"));
+        let one = json!({ "kind": "Literal", "value": { "type": "number", "value": 1.0 } });
+        assert_eq!(
+            read_expression(&json!({ "kind": "Interpolation", "strings": ["x", "y"], "expressions": [one, one] })).err(),
+            Some("Interpolation needs one more string than expressions".to_string())
+        );
+    }
+
+    // A depth that doesn't fit the program reads fine, resolving again
+    // replaces it, and running it unresolved is an error rather than a panic
+    #[test]
+    fn test_json_bogus_depth() {
+        let written = JsonWriter {}.convert_program(&parse("var a = 1; print a;"));
+        let mut program: serde_json::Value = serde_json::from_str(&written).unwrap();
+        program["statements"][1]["expression"]["depth"] = json!(3);
+        let statements = read_program(&program.to_string()).unwrap();
+
+        let interpreter = Interpreter::with_output(Box::new(io::sink()));
+        let error = interpreter.interpret(&statements).err().unwrap();
+        assert_eq!(error.runtime_kind(), Some(&RuntimeErrorKind::InternalError));
+
+        Resolver::new().resolve(&statements).ok().unwrap();
+        assert!(!JsonWriter {}.convert_program(&statements).contains("depth"));
+        assert!(Interpreter::with_output(Box::new(io::sink())).interpret(&statements).is_ok());
+    }
+}
@@ -0,0 +1,26 @@
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+// "and" / "or", kept apart from Binary since the right side may never run
+#[allow(dead_code)]
+pub struct Logical {
+    pub left: Box<dyn Expression>,
+    pub operator: Token<'static>,
+    pub right: Box<dyn Expression>,
+}
+
+impl Expression for Logical {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Logical(self)
+    }
+}
+#[allow(dead_code)]
+impl Logical {
+    pub fn new(left: Box<dyn Expression>, operator: Token<'static>, right: Box<dyn Expression>) -> Logical {
+        Logical {
+            left,
+            operator,
+            right,
+        }
+    }
+}
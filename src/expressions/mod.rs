@@ -1,7 +1,21 @@
+pub mod assign;
 pub mod binary;
+pub mod call;
+pub mod copier;
+pub mod dot;
 pub mod expression;
+pub mod get;
 pub mod grouping;
+pub mod interpolation;
+pub mod json;
 pub mod literal;
+pub mod logical;
 pub mod printer;
+pub mod set;
+pub mod super_expression;
+pub mod this;
+pub mod transformer;
 pub mod unary;
+pub mod unparser;
+pub mod variable;
 pub mod visitor;
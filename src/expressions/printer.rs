@@ -1,66 +1,186 @@
+use super::assign::Assign;
+use super::call::Call;
 use super::expression::Expression;
+use super::get::Get;
+use super::interpolation::Interpolation;
 use super::grouping::Grouping;
-use super::literal::LiteralEnum;
+use super::logical::Logical;
+use super::set::Set;
+use super::super_expression::Super;
+use super::this::This;
+use super::variable::Variable;
 use super::visitor::Visitor;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::Statement;
+use crate::statements::var::Var;
+use crate::statements::while_statement::While;
+use crate::statements::visitor::Visitor as StatementVisitor;
 
 pub struct Printer {}
 
 impl Visitor<String> for Printer {
     fn visit_binary(&self, element: &super::binary::Binary) -> String {
-        let name = element.operator.lexem.clone();
-        let left = &element.left;
-        let right = &element.right;
+        let name = element.operator.lexem.to_string();
+        let left = element.left.as_ref();
+        let right = element.right.as_ref();
         self.parenthesis(&name, &vec![left, right])
     }
     fn visit_literal(&self, element: &super::literal::Literal) -> String {
-        let literal_type = &element.literal_type;
-        match literal_type {
-            LiteralEnum::NIL => return "nil".to_string(),
-            LiteralEnum::BOOLEAN => {
-                return match element.value.downcast_ref::<bool>() {
-                    Some(literal_string) => literal_string.to_string(),
-                    None => panic!("Literal 'Boolean' cannot be parsed"),
-                }
-            }
-            LiteralEnum::STRING => {
-                return match element.value.downcast_ref::<String>() {
-                    Some(literal_string) => literal_string.to_string(),
-                    None => panic!("Literal 'String' cannot be parsed"),
-                }
-            }
-            LiteralEnum::NUMBER => {
-                return match element.value.downcast_ref::<f64>() {
-                    Some(literal_number) => literal_number.to_string(),
-                    None => panic!("Literal 'Number' cannot be parsed"),
-                };
-            }
-        }
+        element.value.to_string()
     }
     fn visit_unary(&self, element: &super::unary::Unary) -> String {
-        let name = element.operator.lexem.clone();
-        let right = &element.right;
+        let name = element.operator.lexem.to_string();
+        let right = element.right.as_ref();
         self.parenthesis(&name, &vec![right])
     }
     fn visit_grouping(&self, element: &Grouping) -> String {
-        self.parenthesis(&"".to_string(), &vec![&element.exp])
+        self.parenthesis(&"".to_string(), &vec![element.exp.as_ref()])
+    }
+    fn visit_variable(&self, element: &Variable) -> String {
+        element.name.lexem.to_string()
+    }
+    fn visit_assign(&self, element: &Assign) -> String {
+        format!("(= {} {})", element.name.lexem, element.value.accept(self))
+    }
+    fn visit_logical(&self, element: &Logical) -> String {
+        let name = element.operator.lexem.to_string();
+        let left = element.left.as_ref();
+        let right = element.right.as_ref();
+        self.parenthesis(&name, &vec![left, right])
+    }
+    fn visit_call(&self, element: &Call) -> String {
+        let mut expressions: Vec<&dyn Expression> = vec![element.callee.as_ref()];
+        for argument in &element.arguments {
+            expressions.push(argument.as_ref());
+        }
+        self.parenthesis(&"call".to_string(), &expressions)
+    }
+    fn visit_get(&self, element: &Get) -> String {
+        format!("(get {} {})", element.object.accept(self), element.name.lexem)
+    }
+    fn visit_set(&self, element: &Set) -> String {
+        format!(
+            "(set {} {} {})",
+            element.object.accept(self),
+            element.name.lexem,
+            element.value.accept(self)
+        )
+    }
+    fn visit_this(&self, _element: &This) -> String {
+        "this".to_string()
+    }
+    fn visit_super(&self, element: &Super) -> String {
+        format!("(super {})", element.method.lexem)
+    }
+    // Empty pieces of string are left out, "${a}" prints as (interpolate a)
+    fn visit_interpolation(&self, element: &Interpolation) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        for (index, string) in element.strings.iter().enumerate() {
+            if !string.is_empty() {
+                parts.push(format!("\"{}\"", string));
+            }
+            if let Some(expression) = element.expressions.get(index) {
+                parts.push(expression.accept(self));
+            }
+        }
+        format!("(interpolate {})", parts.join(" "))
+    }
+}
+
+impl StatementVisitor<String> for Printer {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> String {
+        format!("(; {})", element.expression.accept(self))
+    }
+    fn visit_print(&self, element: &Print) -> String {
+        format!("(print {})", element.expression.accept(self))
+    }
+    fn visit_block(&self, element: &Block) -> String {
+        let mut block = "(block".to_string();
+        for statement in &element.statements {
+            block += " ";
+            block += &statement.accept(self);
+        }
+        block + ")"
+    }
+    fn visit_var(&self, element: &Var) -> String {
+        match &element.initializer {
+            Some(initializer) => format!("(var {} {})", element.name.lexem, initializer.accept(self)),
+            None => format!("(var {})", element.name.lexem),
+        }
+    }
+    fn visit_if(&self, element: &If) -> String {
+        let condition = element.condition.accept(self);
+        let then_branch = element.then_branch.accept(self);
+        match &element.else_branch {
+            Some(else_branch) => format!("(if {} {} {})", condition, then_branch, else_branch.accept(self)),
+            None => format!("(if {} {})", condition, then_branch),
+        }
+    }
+    fn visit_while(&self, element: &While) -> String {
+        format!(
+            "(while {} {})",
+            element.condition.accept(self),
+            element.body.accept(self)
+        )
+    }
+    fn visit_function(&self, element: &Function) -> String {
+        let params: Vec<String> = element.params.iter().map(|param| param.lexem.to_string()).collect();
+        let mut function = format!("(fun {} ({})", element.name.lexem, params.join(" "));
+        for statement in element.body.iter() {
+            function += " ";
+            function += &statement.accept(self);
+        }
+        function + ")"
+    }
+    fn visit_return(&self, element: &Return) -> String {
+        match &element.value {
+            Some(value) => format!("(return {})", value.accept(self)),
+            None => "(return)".to_string(),
+        }
+    }
+    fn visit_class(&self, element: &Class) -> String {
+        let mut class = format!("(class {}", element.name.lexem);
+        if let Some(superclass) = &element.superclass {
+            class += &format!(" < {}", superclass.name.lexem);
+        }
+        for method in &element.methods {
+            class += " ";
+            class += &self.visit_function(method);
+        }
+        class + ")"
     }
 }
 
 #[allow(dead_code)]
 impl Printer {
     pub fn convert(&self, exp: Box<dyn Expression>) -> String {
-        return exp.accept_printer(self);
+        exp.accept(self)
+    }
+
+    // One line per top level statement
+    pub fn convert_program(&self, statements: &[Box<dyn Statement>]) -> String {
+        statements
+            .iter()
+            .map(|statement| statement.accept(self))
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 
-    fn parenthesis(&self, name: &String, expressions: &Vec<&Box<dyn Expression>>) -> String {
+    fn parenthesis(&self, name: &String, expressions: &Vec<&dyn Expression>) -> String {
         let mut expression = format!("({}", name);
         for exp in expressions {
             if !name.is_empty() {
-                expression = expression + " ";
+                expression += " ";
             }
-            expression = expression + exp.accept_printer(self).trim();
+            expression += exp.accept(self).trim();
         }
-        return expression + ")";
+        expression + ")"
     }
 }
 
@@ -69,9 +189,13 @@ mod tests {
     use super::Printer;
     const PRINTER: Printer = Printer {};
     use crate::expressions::grouping::Grouping;
-    use crate::expressions::literal::LiteralEnum;
     use crate::expressions::unary::Unary;
     use crate::token::token_type::Token_Type;
+    use crate::statements::{
+        block::Block, expression_statement::ExpressionStatement, print::Print,
+        statement::Statement,
+    };
+    use crate::value::value::Value;
     use crate::{
         expressions::{binary::Binary, literal::Literal},
         token::token::Token,
@@ -79,14 +203,14 @@ mod tests {
 
     #[test]
     fn test_printer_literal() {
-        let literal = Literal::new(Box::new("Hi".to_string()), LiteralEnum::STRING);
+        let literal = Literal::new(Value::string("Hi"));
         assert_eq!(PRINTER.convert(Box::new(literal)), "Hi")
     }
 
     #[test]
     fn test_printer_binary() {
-        let left_literal = Box::new(Literal::new(Box::new(10 as f64), LiteralEnum::NUMBER));
-        let right_literal = Box::new(Literal::new(Box::new(20 as f64), LiteralEnum::NUMBER));
+        let left_literal = Box::new(Literal::new(Value::Number(10.0)));
+        let right_literal = Box::new(Literal::new(Value::Number(20.0)));
         let operator = Token::new(Token_Type::PLUS, "+".to_string(), "".to_string(), 1);
         let binary = Box::new(Binary::new(left_literal, operator, right_literal));
         assert_eq!(PRINTER.convert(binary), "(+ 10 20)");
@@ -94,15 +218,15 @@ mod tests {
 
     #[test]
     fn test_printer_inner_binary() {
-        let inner_left_literal = Box::new(Literal::new(Box::new(10 as f64), LiteralEnum::NUMBER));
-        let inner_right_literal = Box::new(Literal::new(Box::new(20 as f64), LiteralEnum::NUMBER));
+        let inner_left_literal = Box::new(Literal::new(Value::Number(10.0)));
+        let inner_right_literal = Box::new(Literal::new(Value::Number(20.0)));
         let inner_operator = Token::new(Token_Type::PLUS, "+".to_string(), "".to_string(), 1);
         let inner_binary = Box::new(Binary::new(
             inner_left_literal,
             inner_operator,
             inner_right_literal,
         ));
-        let left_literal = Box::new(Literal::new(Box::new(true), LiteralEnum::BOOLEAN));
+        let left_literal = Box::new(Literal::new(Value::Bool(true)));
         let outer_operator = Token::new(Token_Type::STAR, "*".to_string(), "".to_string(), 1);
         let binary = Box::new(Binary::new(left_literal, outer_operator, inner_binary));
         assert_eq!(PRINTER.convert(binary), "(* true (+ 10 20))");
@@ -111,7 +235,7 @@ mod tests {
     #[test]
     fn test_printer_unary() {
         let operator = Token::new(Token_Type::MINUS, "-".to_string(), "".to_string(), 1);
-        let right_literal = Box::new(Literal::new(Box::new(5 as f64), LiteralEnum::NUMBER));
+        let right_literal = Box::new(Literal::new(Value::Number(5.0)));
         let unary = Box::new(Unary::new(operator, right_literal));
         assert_eq!(PRINTER.convert(unary), "(- 5)");
     }
@@ -119,7 +243,7 @@ mod tests {
     #[test]
     fn test_printer_inner_unary() {
         let inner_operator = Token::new(Token_Type::MINUS, "-".to_string(), "".to_string(), 1);
-        let inner_right_literal = Box::new(Literal::new(Box::new(10 as f64), LiteralEnum::NUMBER));
+        let inner_right_literal = Box::new(Literal::new(Value::Number(10.0)));
         let inner_unary = Box::new(Unary::new(inner_operator, inner_right_literal));
         let operator = Token::new(Token_Type::MINUS, "-".to_string(), "".to_string(), 1);
         let unary = Box::new(Unary::new(operator, inner_unary));
@@ -128,7 +252,7 @@ mod tests {
 
     #[test]
     fn test_printer_grouping() {
-        let exp = Box::new(Literal::new(Box::new(700 as f64), LiteralEnum::NUMBER));
+        let exp = Box::new(Literal::new(Value::Number(700.0)));
         let grouping = Box::new(Grouping::new(exp));
         assert_eq!(PRINTER.convert(grouping), "(700)");
     }
@@ -136,7 +260,7 @@ mod tests {
     #[test]
     fn test_printer_inner_grouping() {
         let operator = Token::new(Token_Type::MINUS, "-".to_string(), "".to_string(), 1);
-        let right_literal = Box::new(Literal::new(Box::new(5 as f64), LiteralEnum::NUMBER));
+        let right_literal = Box::new(Literal::new(Value::Number(5.0)));
         let unary = Box::new(Unary::new(operator, right_literal));
         let grouping = Box::new(Grouping::new(unary));
         assert_eq!(PRINTER.convert(grouping), "((- 5))");
@@ -145,12 +269,12 @@ mod tests {
     #[test]
     fn test_printer_complex() {
         // Example from book
-        let literal_unary = Box::new(Literal::new(Box::new(123 as f64), LiteralEnum::NUMBER));
+        let literal_unary = Box::new(Literal::new(Value::Number(123.0)));
         let unary = Box::new(Unary::new(
             Token::new(Token_Type::MINUS, "-".to_string(), "".to_string(), 1),
             literal_unary,
         ));
-        let literal_grouping = Box::new(Literal::new(Box::new(45.67), LiteralEnum::NUMBER));
+        let literal_grouping = Box::new(Literal::new(Value::Number(45.67)));
         let grouping = Box::new(Grouping::new(literal_grouping));
         let binary = Box::new(Binary::new(
             unary,
@@ -159,4 +283,17 @@ mod tests {
         ));
         assert_eq!(PRINTER.convert(binary), "(* (- 123) (45.67))");
     }
+
+    #[test]
+    fn test_printer_statements() {
+        let print = Box::new(Print::new(Box::new(Literal::new(Value::Number(1.0)))));
+        let expression = Box::new(ExpressionStatement::new(Box::new(Literal::new(Value::Nil))));
+        let block = Box::new(Block::new(vec![print, expression]));
+        let outer_print = Box::new(Print::new(Box::new(Literal::new(Value::string("done")))));
+        let program: Vec<Box<dyn Statement>> = vec![block, outer_print];
+        assert_eq!(
+            PRINTER.convert_program(&program),
+            "(block (print 1) (; nil))\n(print done)"
+        );
+    }
 }
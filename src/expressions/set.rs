@@ -0,0 +1,26 @@
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+// Property assignment, object.name = value
+#[allow(dead_code)]
+pub struct Set {
+    pub object: Box<dyn Expression>,
+    pub name: Token<'static>,
+    pub value: Box<dyn Expression>,
+}
+
+impl Expression for Set {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Set(self)
+    }
+}
+#[allow(dead_code)]
+impl Set {
+    pub fn new(object: Box<dyn Expression>, name: Token<'static>, value: Box<dyn Expression>) -> Set {
+        Set {
+            object,
+            name,
+            value,
+        }
+    }
+}
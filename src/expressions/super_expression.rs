@@ -0,0 +1,28 @@
+use std::cell::Cell;
+
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Super {
+    pub keyword: Token<'static>,
+    pub method: Token<'static>,
+    // How many scopes out the binding lives, set by the resolver. None is global
+    pub depth: Cell<Option<usize>>,
+}
+
+impl Expression for Super {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Super(self)
+    }
+}
+#[allow(dead_code)]
+impl Super {
+    pub fn new(keyword: Token<'static>, method: Token<'static>) -> Super {
+        Super {
+            keyword,
+            method,
+            depth: Cell::new(None),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::cell::Cell;
+
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct This {
+    pub keyword: Token<'static>,
+    // How many scopes out the binding lives, set by the resolver. None is global
+    pub depth: Cell<Option<usize>>,
+}
+
+impl Expression for This {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::This(self)
+    }
+}
+#[allow(dead_code)]
+impl This {
+    pub fn new(keyword: Token<'static>) -> This {
+        This {
+            keyword,
+            depth: Cell::new(None),
+        }
+    }
+}
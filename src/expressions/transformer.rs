@@ -0,0 +1,204 @@
+use super::expression::{Expression, OwnedExpression};
+use super::{
+    assign::Assign, binary::Binary, call::Call, get::Get, grouping::Grouping, interpolation::Interpolation, literal::Literal,
+    logical::Logical, set::Set, super_expression::Super, this::This, unary::Unary, variable::Variable,
+};
+
+// The counterpart of Visitor for passes that rewrite the tree: each node is
+// handed over by value and whatever comes back takes its place. Every method
+// rebuilds the node unchanged by default, so a pass only overrides the kinds
+// it cares about, and can call rebuild_* itself to transform the children first
+pub trait Transformer {
+    fn transform_expression(&mut self, expression: Box<dyn Expression>) -> Box<dyn Expression> {
+        match expression.into_node() {
+            OwnedExpression::Assign(element) => self.transform_assign(*element),
+            OwnedExpression::Binary(element) => self.transform_binary(*element),
+            OwnedExpression::Call(element) => self.transform_call(*element),
+            OwnedExpression::Get(element) => self.transform_get(*element),
+            OwnedExpression::Grouping(element) => self.transform_grouping(*element),
+            OwnedExpression::Interpolation(element) => self.transform_interpolation(*element),
+            OwnedExpression::Literal(element) => self.transform_literal(*element),
+            OwnedExpression::Logical(element) => self.transform_logical(*element),
+            OwnedExpression::Set(element) => self.transform_set(*element),
+            OwnedExpression::Super(element) => self.transform_super(*element),
+            OwnedExpression::This(element) => self.transform_this(*element),
+            OwnedExpression::Unary(element) => self.transform_unary(*element),
+            OwnedExpression::Variable(element) => self.transform_variable(*element),
+        }
+    }
+    fn transform_binary(&mut self, element: Binary) -> Box<dyn Expression> {
+        Box::new(rebuild_binary(self, element))
+    }
+    fn transform_literal(&mut self, element: Literal) -> Box<dyn Expression> {
+        Box::new(element)
+    }
+    fn transform_unary(&mut self, element: Unary) -> Box<dyn Expression> {
+        Box::new(rebuild_unary(self, element))
+    }
+    fn transform_grouping(&mut self, element: Grouping) -> Box<dyn Expression> {
+        Box::new(rebuild_grouping(self, element))
+    }
+    fn transform_variable(&mut self, element: Variable) -> Box<dyn Expression> {
+        Box::new(element)
+    }
+    fn transform_assign(&mut self, element: Assign) -> Box<dyn Expression> {
+        Box::new(rebuild_assign(self, element))
+    }
+    fn transform_logical(&mut self, element: Logical) -> Box<dyn Expression> {
+        Box::new(rebuild_logical(self, element))
+    }
+    fn transform_call(&mut self, element: Call) -> Box<dyn Expression> {
+        Box::new(rebuild_call(self, element))
+    }
+    fn transform_get(&mut self, element: Get) -> Box<dyn Expression> {
+        Box::new(rebuild_get(self, element))
+    }
+    fn transform_set(&mut self, element: Set) -> Box<dyn Expression> {
+        Box::new(rebuild_set(self, element))
+    }
+    fn transform_this(&mut self, element: This) -> Box<dyn Expression> {
+        Box::new(element)
+    }
+    fn transform_super(&mut self, element: Super) -> Box<dyn Expression> {
+        Box::new(element)
+    }
+    fn transform_interpolation(&mut self, element: Interpolation) -> Box<dyn Expression> {
+        Box::new(rebuild_interpolation(self, element))
+    }
+}
+
+// The rebuild_* functions keep the node and its tokens (and the resolver's
+// depth, if it already ran) and only run the transformer over the children
+
+pub fn rebuild_binary<T: Transformer + ?Sized>(transformer: &mut T, element: Binary) -> Binary {
+    Binary {
+        left: transformer.transform_expression(element.left),
+        operator: element.operator,
+        right: transformer.transform_expression(element.right),
+    }
+}
+
+pub fn rebuild_unary<T: Transformer + ?Sized>(transformer: &mut T, element: Unary) -> Unary {
+    Unary {
+        operator: element.operator,
+        right: transformer.transform_expression(element.right),
+    }
+}
+
+pub fn rebuild_grouping<T: Transformer + ?Sized>(transformer: &mut T, element: Grouping) -> Grouping {
+    Grouping {
+        exp: transformer.transform_expression(element.exp),
+    }
+}
+
+pub fn rebuild_assign<T: Transformer + ?Sized>(transformer: &mut T, element: Assign) -> Assign {
+    Assign {
+        name: element.name,
+        value: transformer.transform_expression(element.value),
+        depth: element.depth,
+    }
+}
+
+pub fn rebuild_logical<T: Transformer + ?Sized>(transformer: &mut T, element: Logical) -> Logical {
+    Logical {
+        left: transformer.transform_expression(element.left),
+        operator: element.operator,
+        right: transformer.transform_expression(element.right),
+    }
+}
+
+pub fn rebuild_call<T: Transformer + ?Sized>(transformer: &mut T, element: Call) -> Call {
+    Call {
+        callee: transformer.transform_expression(element.callee),
+        paren: element.paren,
+        arguments: element
+            .arguments
+            .into_iter()
+            .map(|argument| transformer.transform_expression(argument))
+            .collect(),
+    }
+}
+
+pub fn rebuild_get<T: Transformer + ?Sized>(transformer: &mut T, element: Get) -> Get {
+    Get {
+        object: transformer.transform_expression(element.object),
+        name: element.name,
+    }
+}
+
+pub fn rebuild_set<T: Transformer + ?Sized>(transformer: &mut T, element: Set) -> Set {
+    Set {
+        object: transformer.transform_expression(element.object),
+        name: element.name,
+        value: transformer.transform_expression(element.value),
+    }
+}
+
+pub fn rebuild_interpolation<T: Transformer + ?Sized>(transformer: &mut T, element: Interpolation) -> Interpolation {
+    Interpolation {
+        strings: element.strings,
+        expressions: element
+            .expressions
+            .into_iter()
+            .map(|expression| transformer.transform_expression(expression))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rebuild_binary, Transformer};
+    use crate::expressions::binary::Binary;
+    use crate::expressions::expression::{Expression, ExpressionNode};
+    use crate::expressions::literal::Literal;
+    use crate::expressions::printer::Printer;
+    use crate::parser::parser::Parser;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::statement::OwnedStatement;
+    use crate::token::token_type::Token_Type;
+    use crate::value::value::Value;
+
+    fn parse_expression(code: &str) -> Box<dyn Expression> {
+        let statement = Parser::new(Scanner::new(code)).parse().ok().unwrap().remove(0);
+        match statement.into_node() {
+            OwnedStatement::Expression(statement) => statement.expression,
+            _ => panic!("Expected an expression statement"),
+        }
+    }
+
+    struct Unchanged;
+    impl Transformer for Unchanged {}
+
+    // Adds up numbers once both sides are known, the children first
+    struct ConstantFolding;
+    impl Transformer for ConstantFolding {
+        fn transform_binary(&mut self, element: Binary) -> Box<dyn Expression> {
+            let element = rebuild_binary(self, element);
+            let (left, right) = match (element.left.node(), element.right.node()) {
+                (ExpressionNode::Literal(left), ExpressionNode::Literal(right)) => (left.value.clone(), right.value.clone()),
+                _ => return Box::new(element),
+            };
+            match (left, &element.operator.token_type, right) {
+                (Value::Number(left), Token_Type::PLUS, Value::Number(right)) => Box::new(Literal::new(Value::Number(left + right))),
+                (Value::Number(left), Token_Type::STAR, Value::Number(right)) => Box::new(Literal::new(Value::Number(left * right))),
+                _ => Box::new(element),
+            }
+        }
+    }
+
+    #[test]
+    fn test_transformer_rebuilds_unchanged() {
+        let expression = parse_expression("a.b = f(1 + -2, \"${x} and ${y or z}\");");
+        let before = expression.accept(&Printer {});
+        let after = Unchanged.transform_expression(expression).accept(&Printer {});
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_transformer_constant_folding() {
+        let expression = parse_expression("x + 1 + 2 * 3;");
+        assert_eq!(ConstantFolding.transform_expression(expression).accept(&Printer {}), "(+ (+ x 1) 6)");
+        let expression = parse_expression("(1 + 2) * -(3 + 4);");
+        assert_eq!(ConstantFolding.transform_expression(expression).accept(&Printer {}), "(* (3) (- (7)))");
+    }
+}
@@ -0,0 +1,708 @@
+use super::assign::Assign;
+use super::binary::Binary;
+use super::call::Call;
+use super::expression::Expression;
+use super::get::Get;
+use super::grouping::Grouping;
+use super::interpolation::Interpolation;
+use super::literal::Literal;
+use super::logical::Logical;
+use super::set::Set;
+use super::super_expression::Super;
+use super::this::This;
+use super::unary::Unary;
+use super::variable::Variable;
+use super::visitor::Visitor;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::{Statement, StatementNode};
+use crate::statements::var::Var;
+use crate::statements::visitor::Visitor as StatementVisitor;
+use crate::statements::while_statement::While;
+use crate::token::token_type::Token_Type;
+use crate::value::value::Value;
+
+// The grammar's levels, loosest first, so comparing two tells which binds tighter
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Precedence {
+    Assignment,
+    Or,
+    And,
+    Equality,
+    Comparison,
+    Term,
+    Factor,
+    Unary,
+    Call,
+    Primary,
+}
+
+impl Precedence {
+    fn of(operator: &Token_Type) -> Precedence {
+        match operator {
+            Token_Type::OR => Precedence::Or,
+            Token_Type::AND => Precedence::And,
+            Token_Type::EQUAL_EQUAL | Token_Type::BANG_EQUAL => Precedence::Equality,
+            Token_Type::GREATER | Token_Type::GREATER_EQUAL | Token_Type::LESS | Token_Type::LESS_EQUAL => {
+                Precedence::Comparison
+            }
+            Token_Type::PLUS | Token_Type::MINUS => Precedence::Term,
+            Token_Type::STAR | Token_Type::SLASH => Precedence::Factor,
+            _ => Precedence::Unary,
+        }
+    }
+
+    // The level right above, what the right operand of a left associative
+    // operator has to bind at least as tight as
+    fn next(self) -> Precedence {
+        match self {
+            Precedence::Assignment => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor => Precedence::Unary,
+            Precedence::Unary => Precedence::Call,
+            Precedence::Call | Precedence::Primary => Precedence::Primary,
+        }
+    }
+}
+
+// An expression written out along with how tight its outermost part binds
+pub struct Unparsed {
+    text: String,
+    precedence: Precedence,
+}
+
+impl Unparsed {
+    fn new(text: String, precedence: Precedence) -> Unparsed {
+        Unparsed { text, precedence }
+    }
+
+    // Parentheses only when the spot it goes in needs something tighter
+    fn at_least(self, precedence: Precedence) -> String {
+        if self.precedence < precedence {
+            return format!("({})", self.text);
+        }
+        self.text
+    }
+}
+
+// Writes the tree back as Lox source. Grouping nodes keep the parentheses
+// that were written; any others go in only where the tree could not be read
+// back otherwise, e.g. a tree built by a Transformer. A tree from the parser
+// parses back into the same tree.
+pub struct Unparser {}
+
+impl Visitor<Unparsed> for Unparser {
+    fn visit_binary(&self, element: &Binary) -> Unparsed {
+        let precedence = Precedence::of(&element.operator.token_type);
+        self.infix(precedence, element.left.as_ref(), &element.operator.lexem, element.right.as_ref())
+    }
+    fn visit_literal(&self, element: &Literal) -> Unparsed {
+        match &element.value {
+            Value::String(string) => Unparsed::new(format!("\"{}\"", escape(string)), Precedence::Primary),
+            Value::Number(number) => number_literal(*number),
+            value => Unparsed::new(value.to_string(), Precedence::Primary),
+        }
+    }
+    fn visit_unary(&self, element: &Unary) -> Unparsed {
+        let right = element.right.accept(self).at_least(Precedence::Unary);
+        Unparsed::new(format!("{}{}", element.operator.lexem, right), Precedence::Unary)
+    }
+    fn visit_grouping(&self, element: &Grouping) -> Unparsed {
+        Unparsed::new(format!("({})", element.exp.accept(self).text), Precedence::Primary)
+    }
+    fn visit_variable(&self, element: &Variable) -> Unparsed {
+        Unparsed::new(element.name.lexem.to_string(), Precedence::Primary)
+    }
+    // Right associative, so the value can be another assignment as it is
+    fn visit_assign(&self, element: &Assign) -> Unparsed {
+        let value = element.value.accept(self).at_least(Precedence::Assignment);
+        Unparsed::new(format!("{} = {}", element.name.lexem, value), Precedence::Assignment)
+    }
+    fn visit_logical(&self, element: &Logical) -> Unparsed {
+        let precedence = Precedence::of(&element.operator.token_type);
+        self.infix(precedence, element.left.as_ref(), &element.operator.lexem, element.right.as_ref())
+    }
+    fn visit_call(&self, element: &Call) -> Unparsed {
+        let callee = element.callee.accept(self).at_least(Precedence::Call);
+        let arguments: Vec<String> = element
+            .arguments
+            .iter()
+            .map(|argument| argument.accept(self).at_least(Precedence::Assignment))
+            .collect();
+        Unparsed::new(format!("{}({})", callee, arguments.join(", ")), Precedence::Call)
+    }
+    fn visit_get(&self, element: &Get) -> Unparsed {
+        let object = element.object.accept(self).at_least(Precedence::Call);
+        Unparsed::new(format!("{}.{}", object, element.name.lexem), Precedence::Call)
+    }
+    fn visit_set(&self, element: &Set) -> Unparsed {
+        let object = element.object.accept(self).at_least(Precedence::Call);
+        let value = element.value.accept(self).at_least(Precedence::Assignment);
+        Unparsed::new(format!("{}.{} = {}", object, element.name.lexem, value), Precedence::Assignment)
+    }
+    fn visit_this(&self, _element: &This) -> Unparsed {
+        Unparsed::new("this".to_string(), Precedence::Primary)
+    }
+    fn visit_super(&self, element: &Super) -> Unparsed {
+        Unparsed::new(format!("super.{}", element.method.lexem), Precedence::Primary)
+    }
+    fn visit_interpolation(&self, element: &Interpolation) -> Unparsed {
+        let mut text = "\"".to_string();
+        for (index, string) in element.strings.iter().enumerate() {
+            text += &escape(string);
+            if let Some(expression) = element.expressions.get(index) {
+                text += &format!("${{{}}}", expression.accept(self).text);
+            }
+        }
+        Unparsed::new(text + "\"", Precedence::Primary)
+    }
+}
+
+impl StatementVisitor<String> for Unparser {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> String {
+        format!("{};", element.expression.accept(self).text)
+    }
+    fn visit_print(&self, element: &Print) -> String {
+        format!("print {};", element.expression.accept(self).text)
+    }
+    fn visit_block(&self, element: &Block) -> String {
+        if element.statements.is_empty() {
+            return "{}".to_string();
+        }
+        format!("{{\n{}\n}}", indent(&self.convert_program(&element.statements)))
+    }
+    fn visit_var(&self, element: &Var) -> String {
+        let var = match &element.initializer {
+            Some(initializer) => format!("var {} = {};", element.name.lexem, initializer.accept(self).text),
+            None => format!("var {};", element.name.lexem),
+        };
+        with_doc(&element.doc, var)
+    }
+    fn visit_if(&self, element: &If) -> String {
+        let condition = element.condition.accept(self).text;
+        let else_branch = match &element.else_branch {
+            Some(else_branch) => else_branch,
+            None => return format!("if ({}) {}", condition, element.then_branch.accept(self)),
+        };
+        // The else would go to an if left open at the end of the then branch
+        let then_branch = if ends_with_open_if(element.then_branch.as_ref()) {
+            format!("{{\n{}\n}}", indent(&element.then_branch.accept(self)))
+        } else {
+            element.then_branch.accept(self)
+        };
+        format!("if ({}) {} else {}", condition, then_branch, else_branch.accept(self))
+    }
+    fn visit_while(&self, element: &While) -> String {
+        format!("while ({}) {}", element.condition.accept(self).text, element.body.accept(self))
+    }
+    fn visit_function(&self, element: &Function) -> String {
+        with_doc(&element.doc, format!("fun {}", self.method(element)))
+    }
+    fn visit_return(&self, element: &Return) -> String {
+        match &element.value {
+            Some(value) => format!("return {};", value.accept(self).text),
+            None => "return;".to_string(),
+        }
+    }
+    fn visit_class(&self, element: &Class) -> String {
+        let mut class = format!("class {}", element.name.lexem);
+        if let Some(superclass) = &element.superclass {
+            class += &format!(" < {}", superclass.name.lexem);
+        }
+        let methods: Vec<String> = element
+            .methods
+            .iter()
+            .map(|method| with_doc(&method.doc, self.method(method)))
+            .collect();
+        if methods.is_empty() {
+            class += " {}";
+        } else {
+            class += &format!(" {{\n{}\n}}", indent(&methods.join("\n")));
+        }
+        with_doc(&element.doc, class)
+    }
+}
+
+impl Unparser {
+    pub fn convert(&self, exp: &dyn Expression) -> String {
+        exp.accept(self).text
+    }
+
+    // One statement per line, nested ones indented by four spaces
+    pub fn convert_program(&self, statements: &[Box<dyn Statement>]) -> String {
+        statements
+            .iter()
+            .map(|statement| statement.accept(self))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Left associative: a - b - c is (a - b) - c, so only the right side of
+    // an operator at the same level needs parentheses
+    fn infix(&self, precedence: Precedence, left: &dyn Expression, operator: &str, right: &dyn Expression) -> Unparsed {
+        let left = left.accept(self).at_least(precedence);
+        let right = right.accept(self).at_least(precedence.next());
+        Unparsed::new(format!("{} {} {}", left, operator, right), precedence)
+    }
+
+    // What a function and a method share, everything after fun
+    fn method(&self, element: &Function) -> String {
+        let params: Vec<String> = element.params.iter().map(|param| param.lexem.to_string()).collect();
+        let body = if element.body.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{}\n}}", indent(&self.convert_program(&element.body)))
+        };
+        format!("{}({}) {}", element.name.lexem, params.join(", "), body)
+    }
+}
+
+// The parser only makes non negative, finite numbers. The rest come from
+// passes folding constants and are written as whatever evaluates to them
+fn number_literal(number: f64) -> Unparsed {
+    // 0 / 0 would be a runtime error
+    if number.is_nan() {
+        return Unparsed::new("1e999 - 1e999".to_string(), Precedence::Term);
+    }
+    // Overflows to infinity when scanned
+    let digits = match number.is_infinite() {
+        true => "1e999".to_string(),
+        false => number.abs().to_string(),
+    };
+    if number.is_sign_negative() {
+        return Unparsed::new(format!("-{}", digits), Precedence::Unary);
+    }
+    Unparsed::new(digits, Precedence::Primary)
+}
+
+fn ends_with_open_if(statement: &dyn Statement) -> bool {
+    match statement.node() {
+        StatementNode::If(element) => match &element.else_branch {
+            Some(else_branch) => ends_with_open_if(else_branch.as_ref()),
+            None => true,
+        },
+        StatementNode::While(element) => ends_with_open_if(element.body.as_ref()),
+        _ => false,
+    }
+}
+
+fn with_doc(doc: &Option<String>, declaration: String) -> String {
+    match doc {
+        Some(doc) => {
+            let lines: Vec<String> = doc.split('\n').map(|line| format!("/// {}", line)).collect();
+            format!("{}\n{}", lines.join("\n"), declaration)
+        }
+        None => declaration,
+    }
+}
+
+// Strings never hold a raw new line once written, so indenting every line is safe
+fn indent(text: &str) -> String {
+    text.split('\n')
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// The scanner's escapes the other way around. $ is always escaped, so it
+// can't start a ${ by accident
+fn escape(string: &str) -> String {
+    let mut escaped = String::new();
+    for c in string.chars() {
+        match c {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '$' => escaped += "\\$",
+            '\n' => escaped += "\\n",
+            '\t' => escaped += "\\t",
+            '\r' => escaped += "\\r",
+            '\0' => escaped += "\\0",
+            c if c.is_control() => escaped += &format!("\\u{{{:x}}}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ends_with_open_if, Unparser};
+    use crate::expressions::expression::Expression;
+    use crate::expressions::grouping::Grouping;
+    use crate::expressions::json::JsonWriter;
+    use crate::expressions::literal::Literal;
+    use crate::expressions::transformer::Transformer;
+    use crate::expressions::{
+        assign::Assign, binary::Binary, call::Call, get::Get, interpolation::Interpolation, logical::Logical, set::Set,
+        super_expression::Super, this::This, unary::Unary, variable::Variable,
+    };
+    use crate::parser::parser::Parser;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::transformer::Transformer as StatementTransformer;
+    use crate::statements::{
+        block::Block, class::Class, expression_statement::ExpressionStatement, function::Function, if_statement::If,
+        print::Print, return_statement::Return, statement::Statement, var::Var, while_statement::While,
+    };
+    use crate::token::token::Token;
+    use crate::token::token_type::Token_Type;
+    use crate::value::value::Value;
+    use proptest::prelude::*;
+    use serde_json::Value as Json;
+
+    fn parse(code: &str) -> Result<Vec<Box<dyn Statement>>, String> {
+        match Parser::new(Scanner::new(code)).parse() {
+            Ok(statements) => Ok(statements),
+            Err(errors) => Err(format!("{} does not parse: {}", code, errors[0].message())),
+        }
+    }
+
+    // The tree as JSON without positions, which change with the layout
+    fn shape(statements: &[Box<dyn Statement>]) -> Json {
+        fn strip(json: &mut Json) {
+            match json {
+                Json::Object(object) => {
+                    object.remove("span");
+                    object.remove("line");
+                    object.values_mut().for_each(strip);
+                }
+                Json::Array(array) => array.iter_mut().for_each(strip),
+                _ => {}
+            }
+        }
+        let mut json = Json::Array(statements.iter().map(|statement| statement.accept(&JsonWriter {})).collect());
+        strip(&mut json);
+        json
+    }
+
+    // Drops every Grouping, leaving the unparser to put back what is needed
+    struct StripGroupings;
+    impl Transformer for StripGroupings {
+        fn transform_grouping(&mut self, element: Grouping) -> Box<dyn Expression> {
+            self.transform_expression(element.exp)
+        }
+    }
+    impl StatementTransformer for StripGroupings {}
+
+    fn minimal(code: &str) -> String {
+        let statements = StripGroupings.transform_program(parse(code).unwrap());
+        Unparser {}.convert_program(&statements)
+    }
+
+    #[test]
+    fn test_unparser_minimal_parentheses() {
+        assert_eq!(minimal("(a - b) - c;"), "a - b - c;");
+        assert_eq!(minimal("a - (b - c);"), "a - (b - c);");
+        assert_eq!(minimal("(a * b) + (c / d);"), "a * b + c / d;");
+        assert_eq!(minimal("(a + b) * -(c);"), "(a + b) * -c;");
+        assert_eq!(minimal("-(a.b);"), "-a.b;");
+        assert_eq!(minimal("(-a).b;"), "(-a).b;");
+        assert_eq!(minimal("(f)(x)(y);"), "f(x)(y);");
+        assert_eq!(minimal("a = (b = c);"), "a = b = c;");
+        assert_eq!(minimal("(a = b) or c;"), "(a = b) or c;");
+        assert_eq!(minimal("(a or b) and (c == d);"), "(a or b) and c == d;");
+        assert_eq!(minimal("!(a < b) == (c >= d);"), "!(a < b) == c >= d;");
+        assert_eq!(minimal("(a.b = 1).c = \"${(x)}\";"), "(a.b = 1).c = \"${x}\";");
+    }
+
+    #[test]
+    fn test_unparser_keeps_groupings() {
+        let statements = parse("print ((1 + 2)) * 3;").unwrap();
+        assert_eq!(Unparser {}.convert_program(&statements), "print ((1 + 2)) * 3;");
+    }
+
+    #[test]
+    fn test_unparser_literals() {
+        assert_eq!(minimal("print \"a\\\"b\\\\c$d\\n\\u{1}é\";"), "print \"a\\\"b\\\\c\\$d\\n\\u{1}é\";");
+        assert_eq!(minimal("print 0x10 + 1_000.50 + 2.5e-3;"), "print 16 + 1000.5 + 0.0025;");
+        let literal = |number: f64| Unparser {}.convert(&Literal::new(Value::Number(number)));
+        assert_eq!(literal(-2.0), "-2");
+        assert_eq!(literal(f64::INFINITY), "1e999");
+        assert_eq!(literal(f64::NAN), "1e999 - 1e999");
+        let get = Get::new(Box::new(Literal::new(Value::Number(-2.0))), name("x"));
+        assert_eq!(Unparser {}.convert(&get), "(-2).x");
+    }
+
+    #[test]
+    fn test_unparser_statements() {
+        let code = "/// A point
+class P < Base {
+    /// Made with x
+    init(x) {
+        this.x = x;
+    }
+    empty() {}
+}
+fun f(a, b) {
+    if (a) print a; else {
+        while (b > 0) b = b - 1;
+    }
+    return;
+}
+var x;
+{
+    var y = f(1, 2);
+}";
+        assert_eq!(Unparser {}.convert_program(&parse(code).unwrap()), code);
+    }
+
+    #[test]
+    fn test_unparser_dangling_else() {
+        let inner = If::new(Box::new(Variable::new(name("b"))), Box::new(Print::new(number(1.0))), None);
+        let outer = If::new(
+            Box::new(Variable::new(name("a"))),
+            Box::new(inner),
+            Some(Box::new(Print::new(number(2.0)))),
+        );
+        let program: Vec<Box<dyn Statement>> = vec![Box::new(outer)];
+        let code = Unparser {}.convert_program(&program);
+        assert_eq!(code, "if (a) {\n    if (b) print 1;\n} else print 2;");
+    }
+
+    fn name(name: &str) -> Token<'static> {
+        Token::new(Token_Type::IDENTIFIER, name.to_string(), "", 1)
+    }
+
+    fn number(number: f64) -> Box<dyn Expression> {
+        Box::new(Literal::new(Value::Number(number)))
+    }
+
+    // Trees built by hand, without a Grouping anywhere, for proptest to pick from
+
+    const NAMES: &[&str] = &["a", "b", "foo", "bar_2"];
+    const OPERATORS: &[(&str, Token_Type)] = &[
+        ("or", Token_Type::OR),
+        ("and", Token_Type::AND),
+        ("==", Token_Type::EQUAL_EQUAL),
+        ("!=", Token_Type::BANG_EQUAL),
+        ("<", Token_Type::LESS),
+        ("<=", Token_Type::LESS_EQUAL),
+        (">", Token_Type::GREATER),
+        (">=", Token_Type::GREATER_EQUAL),
+        ("+", Token_Type::PLUS),
+        ("-", Token_Type::MINUS),
+        ("*", Token_Type::STAR),
+        ("/", Token_Type::SLASH),
+    ];
+
+    #[derive(Clone, Debug)]
+    enum Tree {
+        Number(f64),
+        Text(String),
+        Bool(bool),
+        Nil,
+        Name(&'static str),
+        This,
+        Super(&'static str),
+        Unary(bool, Box<Tree>),
+        Binary(usize, Box<Tree>, Box<Tree>),
+        Call(Box<Tree>, Vec<Tree>),
+        Get(Box<Tree>, &'static str),
+        Assign(&'static str, Box<Tree>),
+        Set(Box<Tree>, &'static str, Box<Tree>),
+        Interpolation(Vec<(String, Tree)>, String),
+    }
+
+    #[derive(Clone, Debug)]
+    enum Declaration {
+        Expression(Tree),
+        Print(Tree),
+        Return(Option<Tree>),
+        Var(&'static str, Option<Tree>, Option<String>),
+        Block(Vec<Declaration>),
+        If(Tree, Box<Declaration>, Option<Box<Declaration>>),
+        While(Tree, Box<Declaration>),
+        Function(&'static str, Vec<&'static str>, Vec<Declaration>, Option<String>),
+        Class(&'static str, bool, Vec<(&'static str, Vec<Declaration>)>),
+    }
+
+    fn tree() -> impl Strategy<Value = Tree> {
+        let text = "(?s).{0,5}";
+        let leaf = prop_oneof![
+            (0u32..1000).prop_map(|number| Tree::Number(number as f64)),
+            (0.0..1e300f64).prop_map(Tree::Number),
+            text.prop_map(Tree::Text),
+            any::<bool>().prop_map(Tree::Bool),
+            Just(Tree::Nil),
+            prop::sample::select(NAMES).prop_map(Tree::Name),
+            Just(Tree::This),
+            prop::sample::select(NAMES).prop_map(Tree::Super),
+        ];
+        leaf.prop_recursive(5, 48, 3, move |inner| {
+            prop_oneof![
+                (any::<bool>(), inner.clone()).prop_map(|(bang, right)| Tree::Unary(bang, Box::new(right))),
+                (0..OPERATORS.len(), inner.clone(), inner.clone())
+                    .prop_map(|(operator, left, right)| Tree::Binary(operator, Box::new(left), Box::new(right))),
+                (inner.clone(), prop::collection::vec(inner.clone(), 0..3))
+                    .prop_map(|(callee, arguments)| Tree::Call(Box::new(callee), arguments)),
+                (inner.clone(), prop::sample::select(NAMES)).prop_map(|(object, name)| Tree::Get(Box::new(object), name)),
+                (prop::sample::select(NAMES), inner.clone()).prop_map(|(name, value)| Tree::Assign(name, Box::new(value))),
+                (inner.clone(), prop::sample::select(NAMES), inner.clone())
+                    .prop_map(|(object, name, value)| Tree::Set(Box::new(object), name, Box::new(value))),
+                (prop::collection::vec((text, inner.clone()), 1..3), text)
+                    .prop_map(|(parts, end)| Tree::Interpolation(parts, end)),
+            ]
+        })
+    }
+
+    fn declaration() -> impl Strategy<Value = Declaration> {
+        let doc = prop::option::of("[a-z ]{0,6}(\n[a-z ]{0,6})?");
+        let leaf = prop_oneof![
+            tree().prop_map(Declaration::Expression),
+            tree().prop_map(Declaration::Print),
+            prop::option::of(tree()).prop_map(Declaration::Return),
+            (prop::sample::select(NAMES), prop::option::of(tree()), doc.clone())
+                .prop_map(|(name, initializer, doc)| Declaration::Var(name, initializer, doc)),
+        ];
+        leaf.prop_recursive(3, 24, 3, move |inner| {
+            let body = prop::collection::vec(inner.clone(), 0..3);
+            prop_oneof![
+                body.clone().prop_map(Declaration::Block),
+                (tree(), inner.clone(), prop::option::of(inner.clone())).prop_map(|(condition, then_branch, else_branch)| {
+                    Declaration::If(condition, Box::new(then_branch), else_branch.map(Box::new))
+                }),
+                (tree(), inner.clone()).prop_map(|(condition, body)| Declaration::While(condition, Box::new(body))),
+                (
+                    prop::sample::select(NAMES),
+                    prop::collection::vec(prop::sample::select(NAMES), 0..3),
+                    body.clone(),
+                    doc.clone()
+                )
+                    .prop_map(|(name, params, body, doc)| Declaration::Function(name, params, body, doc)),
+                (
+                    prop::sample::select(NAMES),
+                    any::<bool>(),
+                    prop::collection::vec((prop::sample::select(NAMES), body), 0..3)
+                )
+                    .prop_map(|(name, inherits, methods)| Declaration::Class(name, inherits, methods)),
+            ]
+        })
+    }
+
+    fn build(tree: &Tree) -> Box<dyn Expression> {
+        match tree {
+            Tree::Number(number) => Box::new(Literal::new(Value::Number(*number))),
+            Tree::Text(text) => Box::new(Literal::new(Value::string(text))),
+            Tree::Bool(boolean) => Box::new(Literal::new(Value::Bool(*boolean))),
+            Tree::Nil => Box::new(Literal::new(Value::Nil)),
+            Tree::Name(variable) => Box::new(Variable::new(name(variable))),
+            Tree::This => Box::new(This::new(Token::new(Token_Type::THIS, "this", "", 1))),
+            Tree::Super(method) => Box::new(Super::new(Token::new(Token_Type::SUPER, "super", "", 1), name(method))),
+            Tree::Unary(bang, right) => {
+                let operator = match bang {
+                    true => Token::new(Token_Type::BANG, "!", "", 1),
+                    false => Token::new(Token_Type::MINUS, "-", "", 1),
+                };
+                Box::new(Unary::new(operator, build(right)))
+            }
+            Tree::Binary(index, left, right) => {
+                let (lexeme, token_type) = &OPERATORS[*index];
+                let operator = Token::new(token_type.clone(), *lexeme, "", 1);
+                match token_type {
+                    Token_Type::OR | Token_Type::AND => Box::new(Logical::new(build(left), operator, build(right))),
+                    _ => Box::new(Binary::new(build(left), operator, build(right))),
+                }
+            }
+            Tree::Call(callee, arguments) => Box::new(Call::new(
+                build(callee),
+                Token::new(Token_Type::RIGHT_PAREN, ")", "", 1),
+                arguments.iter().map(build).collect(),
+            )),
+            Tree::Get(object, field) => Box::new(Get::new(build(object), name(field))),
+            Tree::Assign(variable, value) => Box::new(Assign::new(name(variable), build(value))),
+            Tree::Set(object, field, value) => Box::new(Set::new(build(object), name(field), build(value))),
+            Tree::Interpolation(parts, end) => {
+                let mut strings: Vec<String> = parts.iter().map(|(string, _)| string.clone()).collect();
+                strings.push(end.clone());
+                Box::new(Interpolation::new(strings, parts.iter().map(|(_, tree)| build(tree)).collect()))
+            }
+        }
+    }
+
+    fn build_declaration(declaration: &Declaration) -> Box<dyn Statement> {
+        match declaration {
+            Declaration::Expression(tree) => Box::new(ExpressionStatement::new(build(tree))),
+            Declaration::Print(tree) => Box::new(Print::new(build(tree))),
+            Declaration::Return(value) => Box::new(Return::new(
+                Token::new(Token_Type::RETURN, "return", "", 1),
+                value.as_ref().map(build),
+            )),
+            Declaration::Var(variable, initializer, doc) => {
+                Box::new(Var::new(name(variable), initializer.as_ref().map(build), doc.clone()))
+            }
+            Declaration::Block(body) => Box::new(Block::new(body.iter().map(build_declaration).collect())),
+            Declaration::If(condition, then_branch, else_branch) => {
+                let mut then_branch = build_branch(then_branch);
+                // The parser gives the else to an if left open in the then
+                // branch, so only a block can keep it on the outer if
+                if else_branch.is_some() && ends_with_open_if(then_branch.as_ref()) {
+                    then_branch = Box::new(Block::new(vec![then_branch]));
+                }
+                Box::new(If::new(
+                    build(condition),
+                    then_branch,
+                    else_branch.as_ref().map(|else_branch| build_branch(else_branch)),
+                ))
+            }
+            Declaration::While(condition, body) => Box::new(While::new(build(condition), build_branch(body))),
+            Declaration::Function(function, params, body, doc) => Box::new(Function::new(
+                name(function),
+                params.iter().map(|param| name(param)).collect(),
+                body.iter().map(build_declaration).collect(),
+                doc.clone(),
+            )),
+            Declaration::Class(class, inherits, methods) => Box::new(Class::new(
+                name(class),
+                inherits.then(|| Variable::new(name("Base"))),
+                methods
+                    .iter()
+                    .map(|(method, body)| {
+                        Function::new(name(method), Vec::new(), body.iter().map(build_declaration).collect(), None)
+                    })
+                    .collect(),
+                None,
+            )),
+        }
+    }
+
+    // The grammar wants a statement there, a declaration has to sit in a block
+    fn build_branch(declaration: &Declaration) -> Box<dyn Statement> {
+        match declaration {
+            Declaration::Var(..) | Declaration::Function(..) | Declaration::Class(..) => {
+                Box::new(Block::new(vec![build_declaration(declaration)]))
+            }
+            _ => build_declaration(declaration),
+        }
+    }
+
+    proptest! {
+        // Parentheses go in exactly where the tree needs them: reading the
+        // source back and dropping the groupings gives the tree that was written
+        #[test]
+        fn test_unparser_expression_round_trip(tree in tree()) {
+            let expected: Vec<Box<dyn Statement>> = vec![Box::new(ExpressionStatement::new(build(&tree)))];
+            let code = Unparser {}.convert_program(&expected);
+            let parsed = parse(&code).map_err(TestCaseError::fail)?;
+            let stripped = StripGroupings.transform_program(parsed);
+            prop_assert_eq!(shape(&stripped), shape(&expected), "{}", code);
+        }
+
+        // parse(unparse(ast)) == ast for whole programs, docs and all
+        #[test]
+        fn test_unparser_program_round_trip(declarations in prop::collection::vec(declaration(), 1..4)) {
+            let built: Vec<Box<dyn Statement>> = declarations.iter().map(build_declaration).collect();
+            let code = Unparser {}.convert_program(&built);
+            let parsed = parse(&code).map_err(TestCaseError::fail)?;
+            let stripped = StripGroupings.transform_program(parsed);
+            prop_assert_eq!(shape(&stripped), shape(&built), "{}", code);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::cell::Cell;
+
+use super::expression::{Expression, ExpressionNode};
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct Variable {
+    pub name: Token<'static>,
+    // How many scopes out the binding lives, set by the resolver. None is global
+    pub depth: Cell<Option<usize>>,
+}
+
+impl Expression for Variable {
+    fn node(&self) -> ExpressionNode<'_> {
+        ExpressionNode::Variable(self)
+    }
+}
+#[allow(dead_code)]
+impl Variable {
+    pub fn new(name: Token<'static>) -> Variable {
+        Variable {
+            name,
+            depth: Cell::new(None),
+        }
+    }
+}
@@ -1,8 +1,20 @@
-use super::{binary::Binary, grouping::Grouping, literal::Literal, unary::Unary};
+use super::{
+    assign::Assign, binary::Binary, call::Call, get::Get, grouping::Grouping, interpolation::Interpolation, literal::Literal,
+    logical::Logical, set::Set, super_expression::Super, this::This, unary::Unary, variable::Variable,
+};
 
 pub trait Visitor<ReturnType> {
     fn visit_binary(&self, element: &Binary) -> ReturnType;
     fn visit_literal(&self, element: &Literal) -> ReturnType;
     fn visit_unary(&self, element: &Unary) -> ReturnType;
     fn visit_grouping(&self, element: &Grouping) -> ReturnType;
+    fn visit_variable(&self, element: &Variable) -> ReturnType;
+    fn visit_assign(&self, element: &Assign) -> ReturnType;
+    fn visit_logical(&self, element: &Logical) -> ReturnType;
+    fn visit_call(&self, element: &Call) -> ReturnType;
+    fn visit_get(&self, element: &Get) -> ReturnType;
+    fn visit_set(&self, element: &Set) -> ReturnType;
+    fn visit_this(&self, element: &This) -> ReturnType;
+    fn visit_super(&self, element: &Super) -> ReturnType;
+    fn visit_interpolation(&self, element: &Interpolation) -> ReturnType;
 }
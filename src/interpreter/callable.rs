@@ -0,0 +1,15 @@
+use core::fmt;
+use std::rc::Rc;
+
+use super::interpreter::Interpreter;
+use crate::{error::error::LoxError, value::value::Value};
+
+// Anything that can be called from Lox, user defined functions as well as
+// functions implemented in Rust. Display is what `print` shows for the value.
+pub trait LoxCallable: fmt::Debug + fmt::Display {
+    fn name(&self) -> &str;
+    fn arity(&self) -> usize;
+    // The interpreter has already checked the argument count against arity.
+    // Takes the Rc so classes can hand themselves to the instances they create.
+    fn call(self: Rc<Self>, interpreter: &Interpreter, arguments: Vec<Value>) -> Result<Value, LoxError>;
+}
@@ -0,0 +1,210 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    error::error::{LoxError, RuntimeErrorKind},
+    token::token::Token,
+    value::value::Value,
+};
+
+// One scope worth of variables, chained to the scope it is nested in.
+// The global scope is the only one without an enclosing environment.
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment::new()
+    }
+}
+
+#[allow(dead_code)]
+impl Environment {
+    pub fn new() -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: None,
+        }
+    }
+
+    pub fn new_enclosed(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    // Redefining an existing variable is allowed and simply overwrites it
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, LoxError> {
+        match self.lookup(name.lexem.as_ref()) {
+            Some(value) => Ok(value),
+            None => Err(Environment::undefined_variable(name)),
+        }
+    }
+
+    // Same as get but for names that don't come from a token, like "this"
+    pub fn lookup(&self, name: &str) -> Option<Value> {
+        if let Some(value) = self.values.get(name) {
+            return Some(value.clone());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().lookup(name),
+            None => None,
+        }
+    }
+
+    // Unlike define, assigning never creates a new variable
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), LoxError> {
+        if let Some(slot) = self.values.get_mut(name.lexem.as_ref()) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(Environment::undefined_variable(name)),
+        }
+    }
+
+    // The resolver already counted the scopes, so these go straight to the
+    // right environment instead of searching the chain
+    pub fn get_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &Token,
+    ) -> Result<Value, LoxError> {
+        match Environment::lookup_at(environment, distance, &name.lexem, name)? {
+            Some(value) => Ok(value),
+            None => Err(Environment::undefined_variable(name)),
+        }
+    }
+
+    // Looks up name, which may not be the token's own lexeme, e.g. 'this' for
+    // a 'super' token. The token is only there to place errors
+    pub fn lookup_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &str,
+        token: &Token,
+    ) -> Result<Option<Value>, LoxError> {
+        let ancestor = Environment::ancestor(environment, distance, token)?;
+        let value = ancestor.borrow().values.get(name).cloned();
+        Ok(value)
+    }
+
+    pub fn assign_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &Token,
+        value: Value,
+    ) -> Result<(), LoxError> {
+        let ancestor = Environment::ancestor(environment, distance, name)?;
+        let mut ancestor = ancestor.borrow_mut();
+        match ancestor.values.get_mut(name.lexem.as_ref()) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(Environment::undefined_variable(name)),
+        }
+    }
+
+    // Only a tree that wasn't resolved against this program, e.g. one read
+    // with --ast-input, can point past the outermost scope
+    fn ancestor(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        token: &Token,
+    ) -> Result<Rc<RefCell<Environment>>, LoxError> {
+        let mut ancestor = environment.clone();
+        for _ in 0..distance {
+            let enclosing = match &ancestor.borrow().enclosing {
+                Some(enclosing) => enclosing.clone(),
+                None => {
+                    let message = format!("'{}' resolved {} scopes out of reach.", token.lexem, distance);
+                    return Err(LoxError::runtime(RuntimeErrorKind::InternalError, token, &message));
+                }
+            };
+            ancestor = enclosing;
+        }
+        Ok(ancestor)
+    }
+
+    fn undefined_variable(name: &Token) -> LoxError {
+        LoxError::runtime(
+            RuntimeErrorKind::UndefinedVariable,
+            name,
+            &format!("Undefined variable '{}'.", name.lexem),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::Environment;
+    use crate::error::error::RuntimeErrorKind;
+    use crate::token::{token::Token, token_type::Token_Type};
+    use crate::value::value::Value;
+
+    fn identifier(name: &str) -> Token<'static> {
+        Token::new(Token_Type::IDENTIFIER, name.to_string(), "".to_string(), 1)
+    }
+
+    #[test]
+    fn test_environment_define_and_get() {
+        let mut environment = Environment::new();
+        environment.define("a", Value::Number(1.0));
+        assert_eq!(environment.get(&identifier("a")).ok().unwrap(), Value::Number(1.0));
+        environment.define("a", Value::Nil);
+        assert_eq!(environment.get(&identifier("a")).ok().unwrap(), Value::Nil);
+    }
+
+    #[test]
+    fn test_environment_enclosing() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals.borrow_mut().define("a", Value::Number(1.0));
+        let mut local = Environment::new_enclosed(globals.clone());
+        local.define("b", Value::Number(2.0));
+
+        assert_eq!(local.get(&identifier("a")).ok().unwrap(), Value::Number(1.0));
+        local.assign(&identifier("a"), Value::Number(3.0)).ok().unwrap();
+        assert_eq!(globals.borrow().get(&identifier("a")).ok().unwrap(), Value::Number(3.0));
+        assert!(globals.borrow().get(&identifier("b")).is_err());
+    }
+
+    #[test]
+    fn test_environment_undefined() {
+        let mut environment = Environment::new();
+        let error = environment.get(&identifier("missing")).err().unwrap();
+        assert_eq!(error.to_string(), "[line 1] Error at 'missing': Undefined variable 'missing'.");
+        assert!(environment.assign(&identifier("missing"), Value::Nil).is_err());
+    }
+
+    #[test]
+    fn test_environment_at_distance() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals.borrow_mut().define("a", Value::Number(1.0));
+        let middle = Rc::new(RefCell::new(Environment::new_enclosed(globals.clone())));
+        middle.borrow_mut().define("a", Value::Number(2.0));
+        let local = Rc::new(RefCell::new(Environment::new_enclosed(middle.clone())));
+
+        assert_eq!(Environment::get_at(&local, 1, &identifier("a")).ok().unwrap(), Value::Number(2.0));
+        assert_eq!(Environment::get_at(&local, 2, &identifier("a")).ok().unwrap(), Value::Number(1.0));
+        assert!(Environment::get_at(&local, 0, &identifier("a")).is_err());
+
+        Environment::assign_at(&local, 2, &identifier("a"), Value::Nil).ok().unwrap();
+        assert_eq!(globals.borrow().get(&identifier("a")).ok().unwrap(), Value::Nil);
+        assert_eq!(middle.borrow().get(&identifier("a")).ok().unwrap(), Value::Number(2.0));
+
+        // A distance past the globals is an error, not a panic
+        let error = Environment::get_at(&local, 3, &identifier("a")).err().unwrap();
+        assert_eq!(error.runtime_kind(), Some(&RuntimeErrorKind::InternalError));
+        assert!(Environment::assign_at(&local, 3, &identifier("a"), Value::Nil).is_err());
+    }
+}
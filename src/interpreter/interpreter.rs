@@ -1,68 +1,921 @@
-use std::any::Any;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use super::{
+    callable::LoxCallable, environment::Environment, lox_class::LoxClass,
+    lox_function::LoxFunction, lox_instance::LoxInstance, native::NativeFunction, unwind::Unwind,
+};
 
 use crate::{
-    expressions::{expression::Expression, literal::{Literal, LiteralEnum}, visitor::Visitor},
-    token::{token_type::Token_Type, token::Token}, lox::Lox,
+    error::error::{LoxError, RuntimeErrorKind},
+    expressions::{
+        assign::Assign, binary::Binary, call::Call, expression::Expression, get::Get,
+        grouping::Grouping, interpolation::Interpolation, literal::Literal, logical::Logical,
+        set::Set, super_expression::Super, this::This, unary::Unary, variable::Variable,
+        visitor::Visitor,
+    },
+    statements::{
+        block::Block, class::Class, expression_statement::ExpressionStatement, function::Function,
+        if_statement::If, print::Print, return_statement::Return, statement::Statement, var::Var,
+        visitor::Visitor as StatementVisitor, while_statement::While,
+    },
+    token::{token::Token, token_type::Token_Type},
+    value::value::Value,
 };
 
-pub struct Interpreter {}
+pub struct Interpreter {
+    // Where print statements write to, stdout unless told otherwise
+    output: RefCell<Box<dyn Write>>,
+    // The outermost scope, where unresolved names are looked up
+    globals: Rc<RefCell<Environment>>,
+    // The innermost scope, swapped out while a block runs
+    environment: RefCell<Rc<RefCell<Environment>>>,
+}
+
+impl StatementVisitor<Result<(), Unwind>> for Interpreter {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> Result<(), Unwind> {
+        match self.evaluate(element.expression.as_ref()) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn visit_print(&self, element: &Print) -> Result<(), Unwind> {
+        let value = self.evaluate(element.expression.as_ref())?;
+        writeln!(self.output.borrow_mut(), "{}", value).expect("Unable to write output");
+        Ok(())
+    }
+
+    fn visit_block(&self, element: &Block) -> Result<(), Unwind> {
+        let enclosing = self.environment.borrow().clone();
+        let block_environment = Environment::new_enclosed(enclosing);
+        self.execute_block(&element.statements, block_environment)
+    }
+
+    fn visit_var(&self, element: &Var) -> Result<(), Unwind> {
+        // Variables without an initializer start out as nil
+        let value = match &element.initializer {
+            Some(initializer) => self.evaluate(initializer.as_ref())?,
+            None => Value::Nil,
+        };
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&element.name.lexem, value);
+        Ok(())
+    }
 
-impl Visitor<Result<Literal, String>> for Interpreter {
-    fn visit_literal(&self, element: &crate::expressions::literal::Literal) -> Result<Literal, String> {
-        Ok(element.clone())
+    fn visit_if(&self, element: &If) -> Result<(), Unwind> {
+        let condition = self.evaluate(element.condition.as_ref())?;
+        if condition.is_truthy() {
+            return self.execute(element.then_branch.as_ref());
+        }
+        match &element.else_branch {
+            Some(else_branch) => self.execute(else_branch.as_ref()),
+            None => Ok(()),
+        }
     }
 
-    fn visit_grouping(&self, element: &crate::expressions::grouping::Grouping) -> Result<Literal, String> {
-        self.evaluate(&element.exp)
+    fn visit_while(&self, element: &While) -> Result<(), Unwind> {
+        loop {
+            let condition = self.evaluate(element.condition.as_ref())?;
+            if !condition.is_truthy() {
+                return Ok(());
+            }
+            match self.execute(element.body.as_ref()) {
+                Ok(_) => {}
+                Err(error) => return Err(error),
+            }
+        }
     }
 
-    fn visit_unary(&self, element: &crate::expressions::unary::Unary) -> Result<Literal, String> {
+    fn visit_function(&self, element: &Function) -> Result<(), Unwind> {
+        // The function closes over the scope it is declared in
+        let closure = self.environment.borrow().clone();
+        let function = LoxFunction::new(element, closure, false);
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&element.name.lexem, Value::Callable(Rc::new(function)));
+        Ok(())
+    }
+
+    fn visit_return(&self, element: &Return) -> Result<(), Unwind> {
+        let value = match &element.value {
+            Some(value) => self.evaluate(value.as_ref())?,
+            None => Value::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_class(&self, element: &Class) -> Result<(), Unwind> {
+        let mut superclass: Option<Rc<LoxClass>> = None;
+        if let Some(variable) = &element.superclass {
+            match self.visit_variable(variable) {
+                Ok(Value::Class(class)) => superclass = Some(class),
+                Ok(_) => {
+                    return Err(self
+                        .runtime_error(
+                            RuntimeErrorKind::TypeError,
+                            &variable.name,
+                            "Superclass must be a class.",
+                        )
+                        .into())
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        let environment = self.environment.borrow().clone();
+        environment.borrow_mut().define(&element.name.lexem, Value::Nil);
+
+        // Methods of a subclass close over an extra scope holding 'super'
+        let mut method_closure = environment.clone();
+        if let Some(superclass) = &superclass {
+            method_closure = Rc::new(RefCell::new(Environment::new_enclosed(environment.clone())));
+            method_closure
+                .borrow_mut()
+                .define("super", Value::Class(superclass.clone()));
+        }
+
+        let mut methods: HashMap<String, Rc<LoxFunction>> = HashMap::new();
+        for method in &element.methods {
+            let is_initializer = method.name.lexem == "init";
+            let function = LoxFunction::new(method, method_closure.clone(), is_initializer);
+            methods.insert(method.name.lexem.to_string(), Rc::new(function));
+        }
+
+        let class = LoxClass::new(&element.name.lexem, superclass, methods);
+        let assigned = environment
+            .borrow_mut()
+            .assign(&element.name, Value::Class(Rc::new(class)));
+        match assigned {
+            Ok(()) => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl Visitor<Result<Value, LoxError>> for Interpreter {
+    fn visit_literal(&self, element: &Literal) -> Result<Value, LoxError> {
+        Ok(element.value.clone())
+    }
+
+    fn visit_grouping(&self, element: &Grouping) -> Result<Value, LoxError> {
+        self.evaluate(element.exp.as_ref())
+    }
+
+    fn visit_unary(&self, element: &Unary) -> Result<Value, LoxError> {
         let operator: &Token_Type = &element.operator.token_type;
         let right = &element.right;
-        let expression: Literal = match self.evaluate(right) {
-            Ok(literal) => literal,
-            Err(m) => return Err(m)
-        };
+        let expression: Value = self.evaluate(right.as_ref())?;
 
         match operator {
-            Token_Type::BANG => {
-                let new_operand = !self.is_truthy(&expression);
-                return Ok(Literal::new(Box::new(new_operand), LiteralEnum::BOOLEAN));
+            Token_Type::BANG => Ok(Value::Bool(!expression.is_truthy())),
+            Token_Type::MINUS => match expression {
+                Value::Number(number) => Ok(Value::Number(-number)),
+                _ => Err(self.runtime_error(
+                    RuntimeErrorKind::TypeError,
+                    &element.operator,
+                    "Operand must be a number.",
+                )),
+            },
+            _ => Err(self.runtime_error(
+                RuntimeErrorKind::TypeError,
+                &element.operator,
+                "Invalid Unary, should not have occured",
+            )),
+        }
+    }
+
+    fn visit_variable(&self, element: &Variable) -> Result<Value, LoxError> {
+        self.look_up_variable(&element.name, element.depth.get())
+    }
+
+    fn visit_assign(&self, element: &Assign) -> Result<Value, LoxError> {
+        let value = self.evaluate(element.value.as_ref())?;
+        let assigned = match element.depth.get() {
+            Some(distance) => Environment::assign_at(
+                &self.environment.borrow(),
+                distance,
+                &element.name,
+                value.clone(),
+            ),
+            None => self.globals.borrow_mut().assign(&element.name, value.clone()),
+        };
+        match assigned {
+            Ok(_) => Ok(value),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Returns whichever operand decided the result, not a coerced bool
+    fn visit_logical(&self, element: &Logical) -> Result<Value, LoxError> {
+        let left = self.evaluate(element.left.as_ref())?;
+        if element.operator.token_type == Token_Type::OR {
+            if left.is_truthy() {
+                return Ok(left);
             }
-            Token_Type::MINUS => {
-                // Check that it's a number
-                if expression.literal_type != LiteralEnum::NUMBER {
-                    return Err(self.runtime_error(&element.operator, "Operand must be a number."))
-                }
-                let value = expression.value.downcast_ref::<f64>().unwrap().clone() * -1.0;
-                return Ok(Literal::new(Box::new(value), LiteralEnum::NUMBER))
+        } else if !left.is_truthy() {
+            return Ok(left);
+        }
+        self.evaluate(element.right.as_ref())
+    }
+
+    fn visit_call(&self, element: &Call) -> Result<Value, LoxError> {
+        let callee = self.evaluate(element.callee.as_ref())?;
+
+        let mut arguments: Vec<Value> = Vec::new();
+        for argument in &element.arguments {
+            match self.evaluate(argument.as_ref()) {
+                Ok(value) => arguments.push(value),
+                Err(error) => return Err(error),
+            }
+        }
+
+        let callable: Rc<dyn LoxCallable> = match callee {
+            Value::Callable(callable) => callable,
+            Value::Class(class) => class,
+            _ => {
+                return Err(self.runtime_error(
+                    RuntimeErrorKind::TypeError,
+                    &element.paren,
+                    "Can only call functions and classes.",
+                ))
             }
-            _ => Err(self.runtime_error(&element.operator, "Invalid Unary, should not have occured"))
+        };
+        if arguments.len() != callable.arity() {
+            return Err(self.runtime_error(
+                RuntimeErrorKind::ArityMismatch,
+                &element.paren,
+                &format!(
+                    "Expected {} arguments but got {} when calling '{}'.",
+                    callable.arity(),
+                    arguments.len(),
+                    callable.name()
+                ),
+            ));
         }
+        callable.call(self, arguments)
     }
 
-    fn visit_binary(&self, element: &crate::expressions::binary::Binary) -> Result<Literal, String> {
-        panic!()
+    fn visit_get(&self, element: &Get) -> Result<Value, LoxError> {
+        let object = self.evaluate(element.object.as_ref())?;
+        match object {
+            Value::Instance(instance) => LoxInstance::get(&instance, &element.name),
+            _ => Err(self.runtime_error(
+                RuntimeErrorKind::TypeError,
+                &element.name,
+                "Only instances have properties.",
+            )),
+        }
     }
 
+    fn visit_set(&self, element: &Set) -> Result<Value, LoxError> {
+        let object = self.evaluate(element.object.as_ref())?;
+        let instance = match object {
+            Value::Instance(instance) => instance,
+            _ => {
+                return Err(self.runtime_error(
+                    RuntimeErrorKind::TypeError,
+                    &element.name,
+                    "Only instances have fields.",
+                ))
+            }
+        };
+
+        let value = self.evaluate(element.value.as_ref())?;
+        instance.borrow_mut().set(&element.name, value.clone());
+        Ok(value)
+    }
+
+    fn visit_this(&self, element: &This) -> Result<Value, LoxError> {
+        self.look_up_variable(&element.keyword, element.depth.get())
+    }
+
+    // Values are turned into text the same way print does it
+    fn visit_interpolation(&self, element: &Interpolation) -> Result<Value, LoxError> {
+        let mut result = String::new();
+        for (string, expression) in element.strings.iter().zip(&element.expressions) {
+            result += string;
+            match self.evaluate(expression.as_ref()) {
+                Ok(value) => result += &value.to_string(),
+                Err(error) => return Err(error),
+            }
+        }
+        if let Some(last) = element.strings.last() {
+            result += last;
+        }
+        Ok(Value::string(&result))
+    }
+
+    fn visit_super(&self, element: &Super) -> Result<Value, LoxError> {
+        // The resolver only lets 'super' appear inside methods of subclasses,
+        // a tree that wasn't resolved against this program may not
+        let misplaced = || {
+            self.runtime_error(
+                RuntimeErrorKind::InternalError,
+                &element.keyword,
+                "'super' is not bound to a superclass here.",
+            )
+        };
+        let distance = element.depth.get().ok_or_else(misplaced)?;
+        let environment = self.environment.borrow().clone();
+        let superclass = match Environment::lookup_at(&environment, distance, "super", &element.keyword)? {
+            Some(Value::Class(class)) => class,
+            _ => return Err(misplaced()),
+        };
+        // The instance is bound one scope inside the one holding 'super'
+        let instance = match distance.checked_sub(1) {
+            Some(distance) => Environment::lookup_at(&environment, distance, "this", &element.keyword)?,
+            None => None,
+        };
+        let instance = instance.ok_or_else(misplaced)?;
+
+        match superclass.find_method(element.method.lexem.as_ref()) {
+            Some(method) => Ok(Value::Callable(Rc::new(method.bind(instance)))),
+            None => Err(self.runtime_error(
+                RuntimeErrorKind::UndefinedProperty,
+                &element.method,
+                &format!("Undefined property '{}'.", element.method.lexem),
+            )),
+        }
+    }
+
+    fn visit_binary(&self, element: &Binary) -> Result<Value, LoxError> {
+        let left: Value = self.evaluate(element.left.as_ref())?;
+        let right: Value = self.evaluate(element.right.as_ref())?;
+        let operator = &element.operator;
+
+        match operator.token_type {
+            // Equality works across every type, values of different types are never equal
+            Token_Type::EQUAL_EQUAL => Ok(Value::Bool(left == right)),
+            Token_Type::BANG_EQUAL => Ok(Value::Bool(left != right)),
+            // Plus is either addition or string concatenation
+            Token_Type::PLUS => match (left, right) {
+                (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left + right)),
+                (Value::String(left), Value::String(right)) => {
+                    Ok(Value::string(&format!("{}{}", left, right)))
+                }
+                _ => Err(self.runtime_error(
+                    RuntimeErrorKind::TypeError,
+                    operator,
+                    "Operands must be two numbers or two strings.",
+                )),
+            },
+            // Everything else needs two numbers
+            _ => {
+                let (left_number, right_number) =
+                    self.number_operands(operator, &left, &right)?;
+                match operator.token_type {
+                    Token_Type::MINUS => Ok(Value::Number(left_number - right_number)),
+                    Token_Type::STAR => Ok(Value::Number(left_number * right_number)),
+                    Token_Type::SLASH => {
+                        if right_number == 0.0 {
+                            return Err(self.runtime_error(
+                                RuntimeErrorKind::DivisionByZero,
+                                operator,
+                                "Division by zero.",
+                            ));
+                        }
+                        Ok(Value::Number(left_number / right_number))
+                    }
+                    Token_Type::GREATER => Ok(Value::Bool(left_number > right_number)),
+                    Token_Type::GREATER_EQUAL => Ok(Value::Bool(left_number >= right_number)),
+                    Token_Type::LESS => Ok(Value::Bool(left_number < right_number)),
+                    Token_Type::LESS_EQUAL => Ok(Value::Bool(left_number <= right_number)),
+                    _ => Err(self.runtime_error(
+                        RuntimeErrorKind::TypeError,
+                        operator,
+                        "Invalid Binary, should not have occured",
+                    )),
+                }
+            }
+        }
+    }
 }
 
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+#[allow(dead_code)]
 impl Interpreter {
-    fn evaluate(&self, expression: &Box<dyn Expression>) -> Result<Literal, String> {
-        expression.accept_interpreter(self)
+    pub fn new() -> Interpreter {
+        Interpreter::with_output(Box::new(io::stdout()))
+    }
+
+    pub fn with_output(output: Box<dyn Write>) -> Interpreter {
+        // The outermost scope is home to the native functions
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        for native in NativeFunction::globals() {
+            let name = native.name().to_string();
+            globals
+                .borrow_mut()
+                .define(&name, Value::Callable(Rc::new(native)));
+        }
+        Interpreter {
+            output: RefCell::new(output),
+            globals: globals.clone(),
+            environment: RefCell::new(globals),
+        }
+    }
+
+    // Runs every statement in order, stopping at the first runtime error
+    pub fn interpret(&self, statements: &[Box<dyn Statement>]) -> Result<(), LoxError> {
+        for statement in statements {
+            match self.execute(statement.as_ref()) {
+                Ok(_) => {}
+                Err(Unwind::Error(error)) => return Err(*error),
+                // The resolver rejects returns outside of functions, if one
+                // gets through anyway it just ends the program
+                Err(Unwind::Return(_)) => return Ok(()),
+            }
+        }
+        Ok(())
     }
 
-    fn runtime_error(&self, token: &Token, message: &str) -> String {
-        Lox::error_token(token, message);
-        message.to_string()
+    fn execute(&self, statement: &dyn Statement) -> Result<(), Unwind> {
+        statement.accept(self)
     }
 
-    fn is_truthy(&self, literal: &Literal) -> bool {
-        match literal.literal_type {
-            LiteralEnum::NIL => false,
-            LiteralEnum::BOOLEAN => literal.value.downcast_ref::<bool>().unwrap().clone(),
-            _ => true
+    // Runs the statements inside the given scope, restoring the current one
+    // afterwards even if a runtime error cut the block short
+    pub fn execute_block(
+        &self,
+        statements: &[Box<dyn Statement>],
+        environment: Environment,
+    ) -> Result<(), Unwind> {
+        let previous = self
+            .environment
+            .replace(Rc::new(RefCell::new(environment)));
+        let mut result = Ok(());
+        for statement in statements {
+            result = self.execute(statement.as_ref());
+            if result.is_err() {
+                break;
+            }
+        }
+        self.environment.replace(previous);
+        result
+    }
+
+    // Resolved names sit a known number of scopes out, the rest are globals
+    fn look_up_variable(&self, name: &Token, depth: Option<usize>) -> Result<Value, LoxError> {
+        match depth {
+            Some(distance) => Environment::get_at(&self.environment.borrow(), distance, name),
+            None => self.globals.borrow().get(name),
         }
     }
 
+    fn evaluate(&self, expression: &dyn Expression) -> Result<Value, LoxError> {
+        expression.accept(self)
+    }
+
+    fn runtime_error(&self, kind: RuntimeErrorKind, token: &Token, message: &str) -> LoxError {
+        LoxError::runtime(kind, token, message)
+    }
+
+    fn number_operands(
+        &self,
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<(f64, f64), LoxError> {
+        match (left, right) {
+            (Value::Number(left), Value::Number(right)) => Ok((*left, *right)),
+            _ => Err(self.runtime_error(
+                RuntimeErrorKind::TypeError,
+                operator,
+                "Operands must be numbers.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    use super::Interpreter;
+    use crate::{
+        error::error::{LoxError, RuntimeErrorKind},
+        parser::parser::Parser,
+        resolver::resolver::Resolver,
+        scanner::scanner::Scanner,
+    };
+
+    // Collects everything the interpreter prints so tests can inspect it
+    #[derive(Clone)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn interpret_code(code: &str) -> Result<String, LoxError> {
+        let mut parser = Parser::new(Scanner::new(code));
+        let statements = parser.parse().ok().unwrap();
+        Resolver::new().resolve(&statements).ok().unwrap();
+        let output = SharedOutput(Rc::new(RefCell::new(Vec::new())));
+        let interpreter = Interpreter::with_output(Box::new(output.clone()));
+        let result = interpreter.interpret(&statements);
+        let printed = String::from_utf8(output.0.borrow().clone()).unwrap();
+        result.map(|_| printed)
+    }
+
+    fn compare_code_to_output(code: &str, expected: &str) {
+        assert_eq!(interpret_code(code).ok().unwrap(), expected);
+    }
+
+    fn compare_code_to_value(code: &str, expected: &str) {
+        compare_code_to_output(&format!("print {};", code), &format!("{}\n", expected));
+    }
+
+    fn compare_code_to_err(code: &str, kind: RuntimeErrorKind, expected: &str) {
+        let error = interpret_code(&format!("{};", code)).err().unwrap();
+        assert_eq!(error.runtime_kind(), Some(&kind));
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_interpreter_arithmetic() {
+        compare_code_to_value("1 + 2", "3");
+        compare_code_to_value("5 - 7", "-2");
+        compare_code_to_value("3 * 4", "12");
+        compare_code_to_value("7 / 2", "3.5");
+        compare_code_to_value("-(1 + 2) * 3", "-9");
+        compare_code_to_value("2 + 3 * 4 - 6 / 2", "11");
+    }
+
+    #[test]
+    fn test_interpreter_comparison() {
+        compare_code_to_value("1 < 2", "true");
+        compare_code_to_value("2 <= 2", "true");
+        compare_code_to_value("1 > 2", "false");
+        compare_code_to_value("3 >= 4", "false");
+    }
+
+    #[test]
+    fn test_interpreter_equality() {
+        compare_code_to_value("nil == nil", "true");
+        compare_code_to_value("nil == false", "false");
+        compare_code_to_value("true == true", "true");
+        compare_code_to_value("true != false", "true");
+        compare_code_to_value("1 == 1", "true");
+        compare_code_to_value("1 == \"1\"", "false");
+        compare_code_to_value("\"a\" == \"a\"", "true");
+        compare_code_to_value("\"a\" != \"b\"", "true");
+    }
+
+    #[test]
+    fn test_interpreter_strings() {
+        compare_code_to_value("\"Hello\" + \" \" + \"World\"", "Hello World");
+        compare_code_to_value("\"Hello\"", "Hello");
+    }
+
+    #[test]
+    fn test_interpreter_unary() {
+        compare_code_to_value("!nil", "true");
+        compare_code_to_value("!!0", "true");
+        compare_code_to_value("--4", "4");
+    }
+
+    #[test]
+    fn test_interpreter_errors() {
+        compare_code_to_err(
+            "1 + \"a\"",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at '+': Operands must be two numbers or two strings.",
+        );
+        compare_code_to_err(
+            "1 < true",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at '<': Operands must be numbers.",
+        );
+        compare_code_to_err(
+            "\"a\" * 2",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at '*': Operands must be numbers.",
+        );
+        compare_code_to_err(
+            "-\"a\"",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at '-': Operand must be a number.",
+        );
+        compare_code_to_err(
+            "1 / (2 - 2)",
+            RuntimeErrorKind::DivisionByZero,
+            "[line 1] Error at '/': Division by zero.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_statements() {
+        compare_code_to_output("", "");
+        compare_code_to_output("print 1; 2 + 3; print \"two\";", "1\ntwo\n");
+        compare_code_to_output("{ print 1; { print 2; } } print 3;", "1\n2\n3\n");
+    }
+
+    #[test]
+    fn test_interpreter_stops_at_error() {
+        let error = interpret_code("print 1; print -nil; print 2;").err().unwrap();
+        assert_eq!(error.message(), "Operand must be a number.");
+    }
+
+    #[test]
+    fn test_interpreter_variables() {
+        compare_code_to_output("var a = 1; var b = a + 2; print b;", "3\n");
+        compare_code_to_output("var a; print a;", "nil\n");
+        compare_code_to_output("var a = 1; var a = 2; print a;", "2\n");
+        compare_code_to_output("var a = 1; a = 5; print a;", "5\n");
+        compare_code_to_output("var a; var b; a = b = 3; print a + b;", "6\n");
+        compare_code_to_output("var a = 1; print a = 2;", "2\n");
+        compare_code_to_output("var x1 = 1; var x2 = 2; print x1 + x2;", "3\n");
+        // Written with a combining accent, but the same name
+        compare_code_to_output("var café = 1; print cafe\u{301};", "1\n");
+    }
+
+    #[test]
+    fn test_interpreter_scopes() {
+        compare_code_to_output(
+            "var a = \"global\"; { var a = \"inner\"; print a; } print a;",
+            "inner\nglobal\n",
+        );
+        compare_code_to_output("var a = 1; { a = 2; { a = a + 1; } } print a;", "3\n");
+        // Closures keep the binding they saw when they were resolved
+        compare_code_to_output(
+            "var a = \"global\"; { fun showA() { print a; } showA(); var a = \"block\"; showA(); }",
+            "global\nglobal\n",
+        );
+        compare_code_to_output(
+            "{ var a = 1; fun f() { return a; } { var a = 2; print f(); } }",
+            "1\n",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_undefined_variables() {
+        compare_code_to_err(
+            "print missing",
+            RuntimeErrorKind::UndefinedVariable,
+            "[line 1] Error at 'missing': Undefined variable 'missing'.",
+        );
+        compare_code_to_err(
+            "missing = 3",
+            RuntimeErrorKind::UndefinedVariable,
+            "[line 1] Error at 'missing': Undefined variable 'missing'.",
+        );
+        compare_code_to_err(
+            "{ var inner = 1; }\nprint inner",
+            RuntimeErrorKind::UndefinedVariable,
+            "[line 2] Error at 'inner': Undefined variable 'inner'.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_logical() {
+        compare_code_to_value("nil or \"yes\"", "yes");
+        compare_code_to_value("1 or 2", "1");
+        compare_code_to_value("nil and 2", "nil");
+        compare_code_to_value("1 and 2", "2");
+        compare_code_to_value("false or nil", "nil");
+        // The right hand side never runs, so the undefined variable is never read
+        compare_code_to_value("true or missing", "true");
+        compare_code_to_value("false and missing", "false");
+    }
+
+    #[test]
+    fn test_interpreter_if() {
+        compare_code_to_output("if (1 < 2) print \"then\"; else print \"else\";", "then\n");
+        compare_code_to_output("if (nil) print \"then\"; else print \"else\";", "else\n");
+        compare_code_to_output("if (false) print \"then\";", "");
+        compare_code_to_output("if (true) if (false) print 1; else print 2;", "2\n");
+    }
+
+    #[test]
+    fn test_interpreter_loops() {
+        compare_code_to_output("var i = 0; while (i < 3) { print i; i = i + 1; }", "0\n1\n2\n");
+        compare_code_to_output("for (var i = 0; i < 3; i = i + 1) print i;", "0\n1\n2\n");
+        compare_code_to_output(
+            "var a = 0; var b = 1; for (var i = 0; i < 6; i = i + 1) { var t = a; a = b; b = t + b; } print a;",
+            "8\n",
+        );
+        // The loop variable is scoped to the loop
+        compare_code_to_err(
+            "for (var i = 0; i < 1; i = i + 1) {} print i",
+            RuntimeErrorKind::UndefinedVariable,
+            "[line 1] Error at 'i': Undefined variable 'i'.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_functions() {
+        compare_code_to_output("fun add(a, b) { return a + b; } print add(1, 2);", "3\n");
+        compare_code_to_output("fun hi() { print \"hi\"; } hi(); print hi();", "hi\nhi\nnil\n");
+        compare_code_to_output("fun f() { return; print 1; } print f();", "nil\n");
+        compare_code_to_output("fun f() {} print f;", "<fn f>\n");
+        compare_code_to_output(
+            "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } print fib(10);",
+            "55\n",
+        );
+        // Return unwinds through loops and blocks
+        compare_code_to_output(
+            "fun f() { for (var i = 0; ; i = i + 1) { if (i == 3) { return i; } } } print f();",
+            "3\n",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_closures() {
+        compare_code_to_output(
+            "fun makeCounter() { var i = 0; fun count() { i = i + 1; return i; } return count; }
+             var counter = makeCounter(); counter(); print counter();",
+            "2\n",
+        );
+        compare_code_to_output(
+            "fun adder(a) { fun add(b) { return a + b; } return add; } print adder(1)(2);",
+            "3\n",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_natives() {
+        compare_code_to_output("print clock;", "<native fn>\n");
+        compare_code_to_output("print clock() > 0;", "true\n");
+    }
+
+    #[test]
+    fn test_interpreter_call_errors() {
+        compare_code_to_err(
+            "\"not a function\"()",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at ')': Can only call functions and classes.",
+        );
+        compare_code_to_err(
+            "fun add(a, b) { return a + b; } add(1)",
+            RuntimeErrorKind::ArityMismatch,
+            "[line 1] Error at ')': Expected 2 arguments but got 1 when calling 'add'.",
+        );
+        compare_code_to_err(
+            "clock(1)",
+            RuntimeErrorKind::ArityMismatch,
+            "[line 1] Error at ')': Expected 0 arguments but got 1 when calling 'clock'.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_classes() {
+        compare_code_to_output("class A {} print A; print A();", "A\nA instance\n");
+        compare_code_to_output("class A {} var a = A(); a.x = 1; a.x = a.x + 1; print a.x;", "2\n");
+        compare_code_to_output("class A {} var a = A(); print a.x = 3;", "3\n");
+        compare_code_to_output(
+            "class A { greet(name) { return \"hi \" + name; } } print A().greet(\"bob\");",
+            "hi bob\n",
+        );
+        compare_code_to_output(
+            "class A { get() { return this.x; } } var a = A(); a.x = 4; var g = a.get; print g();",
+            "4\n",
+        );
+        // Fields shadow methods
+        compare_code_to_output(
+            "class A { m() { return 1; } } var a = A(); a.m = 2; print a.m;",
+            "2\n",
+        );
+        compare_code_to_output("class A { m() {} } print A().m;", "<fn m>\n");
+    }
+
+    #[test]
+    fn test_interpreter_initializers() {
+        compare_code_to_output(
+            "class P { init(x, y) { this.x = x; this.y = y; } } var p = P(1, 2); print p.x + p.y;",
+            "3\n",
+        );
+        // init always hands back the instance, even on an early return
+        compare_code_to_output(
+            "class A { init() { this.x = 1; return; this.x = 2; } } var a = A(); print a.init(); print a.x;",
+            "A instance\n1\n",
+        );
+        compare_code_to_err(
+            "class P { init(x) {} } P()",
+            RuntimeErrorKind::ArityMismatch,
+            "[line 1] Error at ')': Expected 1 arguments but got 0 when calling 'P'.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_property_errors() {
+        compare_code_to_err(
+            "class A {} A().missing",
+            RuntimeErrorKind::UndefinedProperty,
+            "[line 1] Error at 'missing': Undefined property 'missing'.",
+        );
+        compare_code_to_err(
+            "var a = 1; a.x",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at 'x': Only instances have properties.",
+        );
+        compare_code_to_err(
+            "\"str\".x = 1",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at 'x': Only instances have fields.",
+        );
+        compare_code_to_err(
+            "class A {} A.x",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at 'x': Only instances have properties.",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_inheritance() {
+        compare_code_to_output(
+            "class A { m() { return \"A\"; } } class B < A {} print B().m();",
+            "A\n",
+        );
+        compare_code_to_output(
+            "class A { m() { return \"A\"; } } class B < A { m() { return \"B\" + super.m(); } }
+             class C < B {} print C().m();",
+            "BA\n",
+        );
+        // super.method() is bound to the current instance
+        compare_code_to_output(
+            "class A { init(x) { this.x = x; } } class B < A { init(x) { super.init(x * 2); } }
+             print B(2).x;",
+            "4\n",
+        );
+        // Lookup starts at the class holding the method, not at the instance's class
+        compare_code_to_output(
+            "class A { m() { print \"A\"; } } class B < A { m() { print \"B\"; } t() { super.m(); } }
+             class C < B { m() { print \"C\"; } } C().t();",
+            "A\n",
+        );
+        compare_code_to_output(
+            "class A { m() { return this; } } class B < A { m() { var f = super.m; return f(); } }
+             var b = B(); print b.m() == b;",
+            "true\n",
+        );
+    }
+
+    #[test]
+    fn test_interpreter_inheritance_errors() {
+        compare_code_to_err(
+            "var A = 1; class B < A {} B",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at 'A': Superclass must be a class.",
+        );
+        compare_code_to_err(
+            "fun A() {} class B < A {} B",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at 'A': Superclass must be a class.",
+        );
+        compare_code_to_err(
+            "class A {} class B < A { m() { return super.missing; } } B().m()",
+            RuntimeErrorKind::UndefinedProperty,
+            "[line 1] Error at 'missing': Undefined property 'missing'.",
+        );
+        // Without the resolver 'super' has no depth, which is an error, not a panic
+        let code = "class A { m() {} } class B < A { m() { return super.m; } } B().m();";
+        let statements = Parser::new(Scanner::new(code)).parse().ok().unwrap();
+        let error = Interpreter::new().interpret(&statements).err().unwrap();
+        assert_eq!(error.runtime_kind(), Some(&RuntimeErrorKind::InternalError));
+        assert_eq!(
+            error.to_string(),
+            "[line 1] Error at 'super': 'super' is not bound to a superclass here."
+        );
+    }
+
+    #[test]
+    fn test_interpreter_interpolation() {
+        compare_code_to_output(
+            "var name = \"Bob\"; var age = 41; print \"Hello ${name}, you are ${age + 1}\";",
+            "Hello Bob, you are 42\n",
+        );
+        // Same text as print would give
+        compare_code_to_value("\"${1.5} ${3} ${nil} ${true} ${clock}\"", "1.5 3 nil true <native fn>");
+        compare_code_to_value(
+            "\"${\"nested ${1 + 1}\"} and \\${escaped}\"",
+            "nested 2 and ${escaped}",
+        );
+        compare_code_to_output(
+            "class A {} fun f() {} var a = A(); print \"${A} ${a} ${f}\";",
+            "A A instance <fn f>\n",
+        );
+        compare_code_to_err(
+            "\"${-\"x\"}\"",
+            RuntimeErrorKind::TypeError,
+            "[line 1] Error at '-': Operand must be a number.",
+        );
+    }
 }
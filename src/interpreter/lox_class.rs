@@ -0,0 +1,78 @@
+use core::fmt;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{
+    callable::LoxCallable, interpreter::Interpreter, lox_function::LoxFunction,
+    lox_instance::LoxInstance,
+};
+use crate::{error::error::LoxError, value::value::Value};
+
+// Calling a class creates a new instance and runs its initializer, if any
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Rc<LoxFunction>>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: &str,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, Rc<LoxFunction>>,
+    ) -> LoxClass {
+        LoxClass {
+            name: name.to_string(),
+            superclass,
+            methods,
+        }
+    }
+
+    // Methods of the class itself win over the ones it inherits
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(method.clone());
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.find_method(name),
+            None => None,
+        }
+    }
+}
+
+impl LoxCallable for LoxClass {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // A class takes whatever its initializer takes
+    fn arity(&self) -> usize {
+        match self.find_method("init") {
+            Some(initializer) => initializer.arity(),
+            None => 0,
+        }
+    }
+
+    fn call(self: Rc<Self>, interpreter: &Interpreter, arguments: Vec<Value>) -> Result<Value, LoxError> {
+        let initializer = self.find_method("init");
+        let instance = Value::Instance(Rc::new(RefCell::new(LoxInstance::new(self))));
+        if let Some(initializer) = initializer {
+            match Rc::new(initializer.bind(instance.clone())).call(interpreter, arguments) {
+                Ok(_) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(instance)
+    }
+}
+
+impl fmt::Display for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<class {}>", self.name)
+    }
+}
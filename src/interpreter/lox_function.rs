@@ -0,0 +1,99 @@
+use core::fmt;
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+    callable::LoxCallable, environment::Environment, interpreter::Interpreter, unwind::Unwind,
+};
+use crate::{
+    error::error::LoxError,
+    statements::{function::Function, statement::Statement},
+    token::token::Token,
+    value::value::Value,
+};
+
+// A function declared in Lox, together with the scope it was declared in
+pub struct LoxFunction {
+    name: Token<'static>,
+    params: Vec<Token<'static>>,
+    body: Rc<Vec<Box<dyn Statement>>>,
+    closure: Rc<RefCell<Environment>>,
+    // Initializers always hand back the instance, whatever they return
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: &Function,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> LoxFunction {
+        LoxFunction {
+            name: declaration.name.clone(),
+            params: declaration.params.clone(),
+            body: declaration.body.clone(),
+            closure,
+            is_initializer,
+        }
+    }
+
+    // Turns a method into a bound method by wrapping its closure in a scope
+    // where "this" is the given instance
+    pub fn bind(&self, instance: Value) -> LoxFunction {
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        environment.define("this", instance);
+        LoxFunction {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: self.body.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+
+    fn this(&self) -> Value {
+        self.closure
+            .borrow()
+            .lookup("this")
+            .expect("Initializer must be bound to an instance")
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn name(&self) -> &str {
+        &self.name.lexem
+    }
+
+    fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    fn call(self: Rc<Self>, interpreter: &Interpreter, arguments: Vec<Value>) -> Result<Value, LoxError> {
+        // Every call gets its own scope so recursion doesn't share parameters
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        for (param, argument) in self.params.iter().zip(arguments) {
+            environment.define(&param.lexem, argument);
+        }
+
+        let value = match interpreter.execute_block(&self.body, environment) {
+            Ok(_) => Value::Nil,
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(error)) => return Err(*error),
+        };
+        if self.is_initializer {
+            return Ok(self.this());
+        }
+        Ok(value)
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fn {}>", self.name.lexem)
+    }
+}
+
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fn {}>", self.name.lexem)
+    }
+}
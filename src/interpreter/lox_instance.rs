@@ -0,0 +1,60 @@
+use core::fmt;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::lox_class::LoxClass;
+use crate::{
+    error::error::{LoxError, RuntimeErrorKind},
+    token::token::Token,
+    value::value::Value,
+};
+
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: HashMap<String, Value>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> LoxInstance {
+        LoxInstance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    // Fields shadow methods, methods come back bound to the instance.
+    // Takes the instance as a whole since bound methods need to hold on to it.
+    pub fn get(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Result<Value, LoxError> {
+        if let Some(value) = instance.borrow().fields.get(name.lexem.as_ref()) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(name.lexem.as_ref());
+        match method {
+            Some(method) => {
+                let bound = method.bind(Value::Instance(instance.clone()));
+                Ok(Value::Callable(Rc::new(bound)))
+            }
+            None => Err(LoxError::runtime(
+                RuntimeErrorKind::UndefinedProperty,
+                name,
+                &format!("Undefined property '{}'.", name.lexem),
+            )),
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: Value) {
+        self.fields.insert(name.lexem.to_string(), value);
+    }
+}
+
+impl fmt::Display for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
+
+impl fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
@@ -1 +1,8 @@
-// pub mod interpreter;
+pub mod callable;
+pub mod environment;
+pub mod interpreter;
+pub mod lox_class;
+pub mod lox_function;
+pub mod lox_instance;
+pub mod native;
+pub mod unwind;
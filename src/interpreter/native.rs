@@ -0,0 +1,64 @@
+use core::fmt;
+use std::{
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{callable::LoxCallable, interpreter::Interpreter};
+use crate::{error::error::LoxError, value::value::Value};
+
+// A function implemented in Rust and exposed to Lox as a global
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: fn(&[Value]) -> Value,
+}
+
+impl NativeFunction {
+    pub fn new(name: &str, arity: usize, function: fn(&[Value]) -> Value) -> NativeFunction {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            function,
+        }
+    }
+
+    // Every native the interpreter defines in its global scope
+    pub fn globals() -> Vec<NativeFunction> {
+        vec![NativeFunction::new("clock", 0, clock)]
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(self: Rc<Self>, _interpreter: &Interpreter, arguments: Vec<Value>) -> Result<Value, LoxError> {
+        Ok((self.function)(&arguments))
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+// Seconds since the unix epoch, mostly useful for benchmarking Lox code
+fn clock(_arguments: &[Value]) -> Value {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch");
+    Value::Number(now.as_secs_f64())
+}
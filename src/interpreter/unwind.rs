@@ -0,0 +1,15 @@
+use crate::{error::error::LoxError, value::value::Value};
+
+// Why a statement stopped early. Either a runtime error is on its way up to
+// Lox, or a return statement is on its way up to the function being called.
+// The error is boxed to keep every statement's Result small.
+pub enum Unwind {
+    Error(Box<LoxError>),
+    Return(Value),
+}
+
+impl From<LoxError> for Unwind {
+    fn from(error: LoxError) -> Unwind {
+        Unwind::Error(Box::new(error))
+    }
+}
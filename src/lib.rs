@@ -0,0 +1,14 @@
+// Token_Type follows the book's SCREAMING_CASE names and each module keeps a
+// file of the same name.
+#![allow(clippy::upper_case_acronyms, clippy::module_inception)]
+
+pub mod error;
+pub mod expressions;
+pub mod interpreter;
+pub mod lox;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod statements;
+pub mod token;
+pub mod value;
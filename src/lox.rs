@@ -3,27 +3,80 @@ use std::io;
 use std::io::Write;
 use std::process;
 
-use crate::expressions::expression::Expression;
+use crate::error::diagnostics::{Diagnostics, ErrorFormat};
+use crate::error::error::LoxError;
+use crate::error::error::STATIC_ERROR_EXIT_CODE;
+use crate::expressions::dot::DotWriter;
+use crate::expressions::json::{read_program, JsonWriter};
 use crate::expressions::printer::Printer;
+use crate::expressions::unparser::Unparser;
+use crate::interpreter::interpreter::Interpreter;
 use crate::parser::parser::Parser;
+use crate::resolver::resolver::Resolver;
 use crate::scanner::scanner::Scanner;
-use crate::token::token::Token;
-use crate::token::token_type::Token_Type;
+use crate::statements::statement::Statement;
 
-pub struct Lox {}
+// What Lox::run does with a successfully parsed tree
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum RunMode {
+    Interpret,
+    PrintAst,
+    PrintAstJson,
+    PrintAstDot,
+    PrintAstLox,
+}
+
+pub struct Lox {
+    mode: RunMode,
+    error_format: ErrorFormat,
+    // Kept around so the prompt remembers variables between lines
+    interpreter: Interpreter,
+    diagnostics: Diagnostics,
+}
 
 #[allow(dead_code)]
 impl Lox {
-    pub fn new() -> Lox {
-        return Lox {};
+    pub fn new(mode: RunMode, error_format: ErrorFormat) -> Lox {
+        Lox {
+            mode,
+            error_format,
+            interpreter: Interpreter::new(),
+            diagnostics: Diagnostics::new(),
+        }
     }
 
     pub fn run_file(&mut self, file_name: String) {
         let file_contents = fs::read_to_string(file_name.clone())
-            .expect(&format!("Unable to read file {}", file_name));
+            .unwrap_or_else(|_| panic!("Unable to read file {}", file_name));
         match self.run(&file_contents) {
             Ok(_) => {}
-            Err(_) => process::exit(65),
+            Err(errors) => {
+                self.report(&file_contents, &errors);
+                // Runtime errors only happen once the static passes found nothing
+                process::exit(errors[0].exit_code());
+            }
+        }
+    }
+
+    // Runs a tree written by --print-ast=json, or by any other tool following
+    // notes/ast-json.md, instead of source code
+    pub fn run_ast_file(&mut self, file_name: String) {
+        let file_contents = fs::read_to_string(file_name.clone())
+            .unwrap_or_else(|_| panic!("Unable to read file {}", file_name));
+        let statements = match read_program(&file_contents) {
+            Ok(statements) => statements,
+            Err(message) => {
+                eprintln!("Invalid AST in {}: {}", file_name, message);
+                process::exit(STATIC_ERROR_EXIT_CODE);
+            }
+        };
+        // Spans in the tree point into a source we don't have, so no snippets
+        match self.execute("", &statements) {
+            Ok(_) => {}
+            Err(errors) => {
+                self.report("", &errors);
+                process::exit(errors[0].exit_code());
+            }
         }
     }
 
@@ -40,7 +93,10 @@ impl Lox {
                     if n == 0 {
                         process::exit(1);
                     }
-                    self.run(&input).ok(); // We do not care about errors
+                    // Errors are reported but the prompt keeps going
+                    if let Err(errors) = self.run(&input) {
+                        self.report(&input, &errors);
+                    }
                     input.clear();
                 }
                 Err(e) => {
@@ -51,41 +107,57 @@ impl Lox {
         }
     }
 
-    fn run(&mut self, input: &String) -> Result<String, String> {
+    fn run(&mut self, input: &str) -> Result<(), Vec<LoxError>> {
+        // The parser pulls tokens from the scanner as it goes
         let mut scanner = Scanner::new(input);
-        let tokens: Vec<Token> = match scanner.scan_tokens() {
-            Ok(tokens) => tokens,
-            Err(tokens) => {
-                println!("Ran into some scanner issues, continuing");
-                tokens
-            }
-        };
-
-        let mut parser = Parser::new(&tokens);
-        let expr: Box<dyn Expression> = match parser.parse() {
-            Ok(expr) => expr,
-            Err(m) => return Err(m.to_string()),
-        };
-
-        let printer = Printer {};
-        let string: String = printer.convert(expr);
-        println!("{}", string);
-        Ok(string)
+        let parsed = Parser::new(&mut scanner).parse();
+        // Warnings wait for the errors, if there are any, so they all come out together
+        for warning in scanner.warnings() {
+            self.diagnostics.push(warning.clone());
+        }
+        let statements: Vec<Box<dyn Statement>> = parsed?;
+        self.execute(input, &statements)
     }
 
-    pub fn error(line: usize, message: &str) {
-        Lox::report(line, "", message);
-    }
+    fn execute(&mut self, input: &str, statements: &[Box<dyn Statement>]) -> Result<(), Vec<LoxError>> {
+        let resolver = Resolver::new();
+        match resolver.resolve(statements) {
+            Ok(_) => {}
+            Err(errors) => return Err(errors),
+        };
 
-    pub fn error_token(token: &Token, message: &str) {
-        if token.token_type == Token_Type::EOF {
-            Lox::report(token.line, " at end", message);
-            return;
-        }
-        Lox::report(token.line, &format!(" at '{}'", token.lexem), message);
+        self.diagnostics.emit(input, self.error_format);
+        match self.mode {
+            RunMode::PrintAst => {
+                let printer = Printer {};
+                println!("{}", printer.convert_program(statements));
+            }
+            RunMode::PrintAstJson => {
+                let writer = JsonWriter {};
+                println!("{}", writer.convert_program(statements));
+            }
+            RunMode::PrintAstDot => {
+                let writer = DotWriter::new();
+                println!("{}", writer.convert_program(statements));
+            }
+            RunMode::PrintAstLox => {
+                let unparser = Unparser {};
+                println!("{}", unparser.convert_program(statements));
+            }
+            RunMode::Interpret => {
+                match self.interpreter.interpret(statements) {
+                    Ok(_) => {}
+                    Err(error) => return Err(vec![error]),
+                }
+            }
+        };
+        Ok(())
     }
 
-    fn report(line: usize, context: &str, message: &str) {
-        println!("[line {}] Error {} {}", line, context, message);
+    // Everything goes through the diagnostics so it ends up on stderr in the
+    // format that was asked for
+    fn report(&mut self, source: &str, errors: &[LoxError]) {
+        self.diagnostics.push_errors(errors);
+        self.diagnostics.emit(source, self.error_format);
     }
 }
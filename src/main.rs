@@ -1,21 +1,39 @@
-mod expressions;
-mod lox;
-mod parser;
-mod scanner;
-mod token;
-mod interpreter;
-use lox::Lox;
+use lox::error::diagnostics::ErrorFormat;
+use lox::lox::{Lox, RunMode};
 use std::env;
 
 fn main() {
     // The first args[0] is going to be /target/*/lox
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let mut lox = Lox::new();
-    if args.len() == 1 {
+    let mut mode = RunMode::Interpret;
+    let mut ast_input = false;
+    let mut error_format = ErrorFormat::Human;
+    let mut files: Vec<String> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--print-ast" => mode = RunMode::PrintAst,
+            "--print-ast=json" => mode = RunMode::PrintAstJson,
+            "--print-ast=dot" => mode = RunMode::PrintAstDot,
+            "--print-ast=lox" => mode = RunMode::PrintAstLox,
+            "--ast-input" => ast_input = true,
+            "--error-format=human" => error_format = ErrorFormat::Human,
+            "--error-format=json" => error_format = ErrorFormat::Json,
+            flag if flag.starts_with("--") => {
+                println!("Unknown flag {}, exitting...", flag);
+                return;
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    let mut lox = Lox::new(mode, error_format);
+    if files.is_empty() {
         lox.run_prompt()
-    } else if args.len() == 2 {
-        lox.run_file(args.get(1).unwrap().clone());
+    } else if files.len() == 1 && ast_input {
+        lox.run_ast_file(files.remove(0));
+    } else if files.len() == 1 {
+        lox.run_file(files.remove(0));
     } else {
         println!("More than 1 arg was given, can't proceed, exitting...");
     }
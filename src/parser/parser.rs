@@ -1,53 +1,486 @@
+use crate::error::error::LoxError;
+use crate::expressions::assign::Assign;
 use crate::expressions::binary::Binary;
-use crate::expressions::expression::Expression;
+use crate::expressions::call::Call;
+use crate::expressions::expression::{Expression, OwnedExpression};
+use crate::expressions::get::Get;
 use crate::expressions::grouping::Grouping;
-use crate::expressions::literal::{Literal, LiteralEnum};
+use crate::expressions::interpolation::Interpolation;
+use crate::expressions::literal::Literal;
+use crate::expressions::logical::Logical;
+use crate::expressions::set::Set;
+use crate::expressions::super_expression::Super;
+use crate::expressions::this::This;
 use crate::expressions::unary::Unary;
-use crate::lox::Lox;
+use crate::expressions::variable::Variable;
+use crate::statements::block::Block;
+use crate::statements::class::Class;
+use crate::statements::expression_statement::ExpressionStatement;
+use crate::statements::function::Function;
+use crate::statements::if_statement::If;
+use crate::statements::print::Print;
+use crate::statements::return_statement::Return;
+use crate::statements::statement::Statement;
+use crate::statements::var::Var;
+use crate::statements::while_statement::While;
 use crate::token::token::Token;
 use crate::token::token_type::Token_Type;
+use crate::value::value::Value;
 
+// Functions and calls are capped at this many parameters / arguments
+const MAX_ARGUMENTS: usize = 255;
+
+// Pulls tokens one at a time, usually straight from a Scanner, and keeps
+// only the one it is looking at and the one before
 #[allow(dead_code)]
-pub struct Parser<'a> {
-    tokens: &'a Vec<Token>,
-    current: usize,
+pub struct Parser<'a, I: Iterator<Item = Token<'a>>> {
+    tokens: I,
+    current: Token<'a>,
+    // The last consumed token, ERROR tokens in between are skipped
+    previous: Token<'a>,
+    // Syntax errors seen so far, the parser keeps going after each one
+    errors: Vec<LoxError>,
+    // How many ERROR tokens from the scanner have been reported
+    scan_errors: usize,
 }
 
 #[allow(dead_code)]
-impl<'a> Parser<'a> {
-    pub fn new(tokens: &'a Vec<Token>) -> Parser<'a> {
-        Parser { tokens, current: 0 }
+impl<'a, I: Iterator<Item = Token<'a>>> Parser<'a, I> {
+    pub fn new(mut tokens: I) -> Parser<'a, I> {
+        let current = match tokens.next() {
+            Some(token) => token,
+            None => Token::new(Token_Type::EOF, "", "", 1),
+        };
+        Parser {
+            tokens,
+            current: current.clone(),
+            previous: current,
+            errors: Vec::new(),
+            scan_errors: 0,
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Box<dyn Expression>, String> {
-        self.expression()
+    // program        → declaration* EOF ;
+    // Returns every syntax error in the source if there was at least one
+    pub fn parse(&mut self) -> Result<Vec<Box<dyn Statement>>, Vec<LoxError>> {
+        let mut statements: Vec<Box<dyn Statement>> = Vec::new();
+        self.skip_error_tokens();
+        while !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(statements)
     }
 
-    // equality       → comparison ( ( "!=" | "==" ) comparison )* ;
-    fn expression(&mut self) -> Result<Box<dyn Expression>, String> {
-        let mut expr: Box<dyn Expression> = match self.comparison() {
-            Ok(expr) => expr,
-            Err(m) => return Err(m),
+    // A declaration is where the parser recovers from an error: it is
+    // recorded, the rest of the broken statement is skipped and None is
+    // returned in place of it. If the scanner already choked on something in
+    // this declaration the syntax error is most likely caused by that, so
+    // only the scanner's error is kept.
+    fn declaration(&mut self) -> Option<Box<dyn Statement>> {
+        let scan_errors = self.scan_errors;
+        match self.declaration_or_error() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                if self.scan_errors == scan_errors {
+                    self.errors.push(error);
+                }
+                self.synchronize();
+                None
+            }
+        }
+    }
+
+    // declaration    → classDecl | funDecl | varDecl | statement ;
+    // The scanner hangs /// comments on the token after them, which for a
+    // declaration is its keyword
+    fn declaration_or_error(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        let doc = self.peek().doc.clone();
+        if self.match_token(&vec![Token_Type::CLASS]) {
+            return self.class_declaration(doc);
+        }
+        if self.match_token(&vec![Token_Type::FUN]) {
+            return match self.function("function", doc) {
+                Ok(function) => Ok(Box::new(function)),
+                Err(m) => Err(m),
+            };
+        }
+        if self.match_token(&vec![Token_Type::VAR]) {
+            return self.var_declaration(doc);
+        }
+        self.statement()
+    }
+
+    // classDecl      → "class" IDENTIFIER ( "<" IDENTIFIER )? "{" function* "}" ;
+    fn class_declaration(&mut self, doc: Option<String>) -> Result<Box<dyn Statement>, LoxError> {
+        let name = match self.consume(&Token_Type::IDENTIFIER, "Expect class name.") {
+            Ok(name) => name.clone().into_owned(),
+            Err(err) => return Err(err),
         };
 
-        let expression_vector = vec![Token_Type::BANG_EQUAL, Token_Type::EQUAL_EQUAL];
-        while self.match_token(&expression_vector) {
-            let operator = self.previous().clone();
-            let right = match self.comparison() {
-                Ok(expr) => expr,
+        let mut superclass: Option<Variable> = None;
+        if self.match_token(&vec![Token_Type::LESS]) {
+            // Only a name can refer to a class, anything else can never be one
+            let superclass_name = match self.consume(&Token_Type::IDENTIFIER, "Expect superclass name.") {
+                Ok(superclass_name) => superclass_name.clone().into_owned(),
+                Err(err) => return Err(err),
+            };
+            superclass = Some(Variable::new(superclass_name));
+        }
+
+        match self.consume(&Token_Type::LEFT_BRACE, "Expect '{' before class body.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let mut methods: Vec<Function> = Vec::new();
+        while !self.check(&Token_Type::RIGHT_BRACE) && !self.is_at_end() {
+            // Methods have no keyword, their doc is on the name
+            let method_doc = self.peek().doc.clone();
+            match self.function("method", method_doc) {
+                Ok(method) => methods.push(method),
+                Err(m) => return Err(m),
+            }
+        }
+
+        match self.consume(&Token_Type::RIGHT_BRACE, "Expect '}' after class body.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(Class::new(name, superclass, methods, doc)))
+    }
+
+    // funDecl        → "fun" function ;
+    // function       → IDENTIFIER "(" parameters? ")" block ;
+    // parameters     → IDENTIFIER ( "," IDENTIFIER )* ;
+    // kind is only used to word error messages
+    fn function(&mut self, kind: &str, doc: Option<String>) -> Result<Function, LoxError> {
+        let name = match self.consume(&Token_Type::IDENTIFIER, &format!("Expect {} name.", kind)) {
+            Ok(name) => name.clone().into_owned(),
+            Err(err) => return Err(err),
+        };
+        match self.consume(
+            &Token_Type::LEFT_PAREN,
+            &format!("Expect '(' after {} name.", kind),
+        ) {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let mut params: Vec<Token<'static>> = Vec::new();
+        if !self.check(&Token_Type::RIGHT_PAREN) {
+            loop {
+                // Nothing is confused by this, so report it and carry on
+                if params.len() >= MAX_ARGUMENTS {
+                    let error = self.parser_error(
+                        self.peek(),
+                        &format!("Can't have more than {} parameters.", MAX_ARGUMENTS),
+                    );
+                    self.errors.push(error);
+                }
+                match self.consume(&Token_Type::IDENTIFIER, "Expect parameter name.") {
+                    Ok(param) => params.push(param.clone().into_owned()),
+                    Err(err) => return Err(err),
+                };
+                if !self.match_token(&vec![Token_Type::COMMA]) {
+                    break;
+                }
+            }
+        }
+        match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after parameters.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        match self.consume(
+            &Token_Type::LEFT_BRACE,
+            &format!("Expect '{{' before {} body.", kind),
+        ) {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        let body = self.block()?;
+        Ok(Function::new(name, params, body, doc))
+    }
+
+    // varDecl        → "var" IDENTIFIER ( "=" expression )? ";" ;
+    fn var_declaration(&mut self, doc: Option<String>) -> Result<Box<dyn Statement>, LoxError> {
+        let name = match self.consume(&Token_Type::IDENTIFIER, "Expect variable name.") {
+            Ok(name) => name.clone().into_owned(),
+            Err(err) => return Err(err),
+        };
+
+        let mut initializer: Option<Box<dyn Expression>> = None;
+        if self.match_token(&vec![Token_Type::EQUAL]) {
+            initializer = match self.expression() {
+                Ok(expr) => Some(expr),
+                Err(m) => return Err(m),
+            };
+        }
+
+        match self.consume(
+            &Token_Type::SEMICOLON,
+            "Expect ';' after variable declaration.",
+        ) {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(Var::new(name, initializer, doc)))
+    }
+
+    // statement      → exprStmt | forStmt | ifStmt | printStmt | returnStmt | whileStmt | block ;
+    fn statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        if self.match_token(&vec![Token_Type::FOR]) {
+            return self.for_statement();
+        }
+        if self.match_token(&vec![Token_Type::IF]) {
+            return self.if_statement();
+        }
+        if self.match_token(&vec![Token_Type::PRINT]) {
+            return self.print_statement();
+        }
+        if self.match_token(&vec![Token_Type::RETURN]) {
+            return self.return_statement();
+        }
+        if self.match_token(&vec![Token_Type::WHILE]) {
+            return self.while_statement();
+        }
+        if self.match_token(&vec![Token_Type::LEFT_BRACE]) {
+            return match self.block() {
+                Ok(statements) => Ok(Box::new(Block::new(statements))),
+                Err(m) => Err(m),
+            };
+        }
+        self.expression_statement()
+    }
+
+    // forStmt        → "for" "(" ( varDecl | exprStmt | ";" )
+    //                  expression? ";"
+    //                  expression? ")" statement ;
+    // There is no for node, the loop is desugared into a while loop
+    fn for_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        match self.consume(&Token_Type::LEFT_PAREN, "Expect '(' after 'for'.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let initializer: Option<Box<dyn Statement>> = if self.match_token(&vec![Token_Type::SEMICOLON]) {
+            None
+        } else if self.match_token(&vec![Token_Type::VAR]) {
+            match self.var_declaration(None) {
+                Ok(statement) => Some(statement),
+                Err(m) => return Err(m),
+            }
+        } else {
+            match self.expression_statement() {
+                Ok(statement) => Some(statement),
                 Err(m) => return Err(m),
+            }
+        };
+
+        let mut condition: Option<Box<dyn Expression>> = None;
+        if !self.check(&Token_Type::SEMICOLON) {
+            condition = match self.expression() {
+                Ok(expr) => Some(expr),
+                Err(m) => return Err(m),
+            };
+        }
+        match self.consume(&Token_Type::SEMICOLON, "Expect ';' after loop condition.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let mut increment: Option<Box<dyn Expression>> = None;
+        if !self.check(&Token_Type::RIGHT_PAREN) {
+            increment = match self.expression() {
+                Ok(expr) => Some(expr),
+                Err(m) => return Err(m),
+            };
+        }
+        match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after for clauses.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let mut body = self.statement()?;
+
+        // { initializer; while (condition) { body; increment; } }
+        if let Some(increment) = increment {
+            body = Box::new(Block::new(vec![
+                body,
+                Box::new(ExpressionStatement::new(increment)),
+            ]));
+        }
+        let condition = match condition {
+            Some(condition) => condition,
+            None => Box::new(Literal::new(Value::Bool(true))),
+        };
+        body = Box::new(While::new(condition, body));
+        if let Some(initializer) = initializer {
+            body = Box::new(Block::new(vec![initializer, body]));
+        }
+        Ok(body)
+    }
+
+    // ifStmt         → "if" "(" expression ")" statement ( "else" statement )? ;
+    fn if_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        match self.consume(&Token_Type::LEFT_PAREN, "Expect '(' after 'if'.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        let condition = self.expression()?;
+        match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after if condition.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+
+        let then_branch = self.statement()?;
+        // A dangling else binds to the nearest if
+        let mut else_branch: Option<Box<dyn Statement>> = None;
+        if self.match_token(&vec![Token_Type::ELSE]) {
+            else_branch = match self.statement() {
+                Ok(statement) => Some(statement),
+                Err(m) => return Err(m),
+            };
+        }
+        Ok(Box::new(If::new(condition, then_branch, else_branch)))
+    }
+
+    // whileStmt      → "while" "(" expression ")" statement ;
+    fn while_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        match self.consume(&Token_Type::LEFT_PAREN, "Expect '(' after 'while'.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        let condition = self.expression()?;
+        match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after condition.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        let body = self.statement()?;
+        Ok(Box::new(While::new(condition, body)))
+    }
+
+    // printStmt      → "print" expression ";" ;
+    fn print_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        let value = self.expression()?;
+        match self.consume(&Token_Type::SEMICOLON, "Expect ';' after value.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(Print::new(value)))
+    }
+
+    // returnStmt     → "return" expression? ";" ;
+    fn return_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        let keyword = self.previous().clone().into_owned();
+        let mut value: Option<Box<dyn Expression>> = None;
+        if !self.check(&Token_Type::SEMICOLON) {
+            value = match self.expression() {
+                Ok(expr) => Some(expr),
+                Err(m) => return Err(m),
+            };
+        }
+        match self.consume(&Token_Type::SEMICOLON, "Expect ';' after return value.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(Return::new(keyword, value)))
+    }
+
+    // exprStmt       → expression ";" ;
+    fn expression_statement(&mut self) -> Result<Box<dyn Statement>, LoxError> {
+        let expr = self.expression()?;
+        match self.consume(&Token_Type::SEMICOLON, "Expect ';' after expression.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(ExpressionStatement::new(expr)))
+    }
+
+    // block          → "{" declaration* "}" ;
+    fn block(&mut self) -> Result<Vec<Box<dyn Statement>>, LoxError> {
+        let mut statements: Vec<Box<dyn Statement>> = Vec::new();
+        while !self.check(&Token_Type::RIGHT_BRACE) && !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+        match self.consume(&Token_Type::RIGHT_BRACE, "Expect '}' after block.") {
+            Ok(_) => {}
+            Err(err) => return Err(err),
+        };
+        Ok(statements)
+    }
+
+    // expression     → assignment ;
+    fn expression(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        self.assignment()
+    }
+
+    // assignment     → ( call "." )? IDENTIFIER "=" assignment | logic_or ;
+    fn assignment(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let expr: Box<dyn Expression> = self.or()?;
+
+        if self.match_token(&vec![Token_Type::EQUAL]) {
+            let equals = self.previous().clone().into_owned();
+            // Assignment is right associative so we recurse instead of looping
+            let value = self.assignment()?;
+
+            // Only now do we know the left hand side was meant to be a target
+            return match expr.into_node() {
+                OwnedExpression::Variable(variable) => Ok(Box::new(Assign::new(variable.name, value))),
+                OwnedExpression::Get(get) => Ok(Box::new(Set::new(get.object, get.name, value))),
+                _ => Err(self.parser_error(&equals, "Invalid assignment target.")),
             };
+        }
+        Ok(expr)
+    }
+
+    // logic_or       → logic_and ( "or" logic_and )* ;
+    fn or(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.and()?;
+
+        while self.match_token(&vec![Token_Type::OR]) {
+            let operator = self.previous().clone().into_owned();
+            let right = self.and()?;
+            expr = Box::new(Logical::new(expr, operator, right));
+        }
+        Ok(expr)
+    }
+
+    // logic_and      → equality ( "and" equality )* ;
+    fn and(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.equality()?;
+
+        while self.match_token(&vec![Token_Type::AND]) {
+            let operator = self.previous().clone().into_owned();
+            let right = self.equality()?;
+            expr = Box::new(Logical::new(expr, operator, right));
+        }
+        Ok(expr)
+    }
+
+    // equality       → comparison ( ( "!=" | "==" ) comparison )* ;
+    fn equality(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.comparison()?;
+
+        let expression_vector = vec![Token_Type::BANG_EQUAL, Token_Type::EQUAL_EQUAL];
+        while self.match_token(&expression_vector) {
+            let operator = self.previous().clone().into_owned();
+            let right = self.comparison()?;
             expr = Box::new(Binary::new(expr, operator, right));
         }
         Ok(expr)
     }
 
     // comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
-    fn comparison(&mut self) -> Result<Box<dyn Expression>, String> {
-        let mut expr: Box<dyn Expression> = match self.term() {
-            Ok(expr) => expr,
-            Err(m) => return Err(m),
-        };
+    fn comparison(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.term()?;
 
         let comparison_vector = vec![
             Token_Type::GREATER,
@@ -56,106 +489,150 @@ impl<'a> Parser<'a> {
             Token_Type::LESS_EQUAL,
         ];
         while self.match_token(&comparison_vector) {
-            let operator = self.previous().clone();
-            let right = match self.term() {
-                Ok(expr) => expr,
-                Err(m) => return Err(m),
-            };
+            let operator = self.previous().clone().into_owned();
+            let right = self.term()?;
             expr = Box::new(Binary::new(expr, operator, right))
         }
         Ok(expr)
     }
 
     // term           → factor ( ( "-" | "+" ) factor )* ;
-    fn term(&mut self) -> Result<Box<dyn Expression>, String> {
-        let mut expr: Box<dyn Expression> = match self.factor() {
-            Ok(expr) => expr,
-            Err(m) => return Err(m),
-        };
+    fn term(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.factor()?;
 
         let term_vector = vec![Token_Type::MINUS, Token_Type::PLUS];
         while self.match_token(&term_vector) {
-            let operator = self.previous().clone();
-            let right = match self.factor() {
-                Ok(expr) => expr,
-                Err(m) => return Err(m),
-            };
+            let operator = self.previous().clone().into_owned();
+            let right = self.factor()?;
             expr = Box::new(Binary::new(expr, operator, right))
         }
         Ok(expr)
     }
 
     // factor         → unary ( ( "/" | "*" ) unary )* ;
-    fn factor(&mut self) -> Result<Box<dyn Expression>, String> {
-        let mut expr: Box<dyn Expression> = match self.unary() {
-            Ok(expr) => expr,
-            Err(m) => return Err(m),
-        };
+    fn factor(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.unary()?;
 
         let factor_vector = vec![Token_Type::SLASH, Token_Type::STAR];
         while self.match_token(&factor_vector) {
-            let operator = self.previous().clone();
-            let right = match self.unary() {
-                Ok(right) => right,
-                Err(message) => return Err(message),
-            };
+            let operator = self.previous().clone().into_owned();
+            let right = self.unary()?;
             expr = Box::new(Binary::new(expr, operator, right));
         }
         Ok(expr)
     }
 
-    // unary          → ( "!" | "-" ) unary | primary
-    fn unary(&mut self) -> Result<Box<dyn Expression>, String> {
+    // unary          → ( "!" | "-" ) unary | call
+    fn unary(&mut self) -> Result<Box<dyn Expression>, LoxError> {
         let unary_vector = vec![Token_Type::BANG, Token_Type::MINUS];
         if self.match_token(&unary_vector) {
-            let operator = self.previous().clone();
-            let right = match self.unary() {
-                Ok(right) => right,
-                Err(message) => return Err(message),
-            };
+            let operator = self.previous().clone().into_owned();
+            let right = self.unary()?;
             return Ok(Box::new(Unary::new(operator, right)));
         }
-        self.primary()
+        self.call()
+    }
+
+    // call           → primary ( "(" arguments? ")" | "." IDENTIFIER )* ;
+    fn call(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut expr: Box<dyn Expression> = self.primary()?;
+
+        loop {
+            if self.match_token(&vec![Token_Type::LEFT_PAREN]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(&vec![Token_Type::DOT]) {
+                let name = match self.consume(&Token_Type::IDENTIFIER, "Expect property name after '.'.") {
+                    Ok(name) => name.clone().into_owned(),
+                    Err(err) => return Err(err),
+                };
+                expr = Box::new(Get::new(expr, name));
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    // arguments      → expression ( "," expression )* ;
+    fn finish_call(&mut self, callee: Box<dyn Expression>) -> Result<Box<dyn Expression>, LoxError> {
+        let mut arguments: Vec<Box<dyn Expression>> = Vec::new();
+        if !self.check(&Token_Type::RIGHT_PAREN) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    let error = self.parser_error(
+                        self.peek(),
+                        &format!("Can't have more than {} arguments.", MAX_ARGUMENTS),
+                    );
+                    self.errors.push(error);
+                }
+                match self.expression() {
+                    Ok(argument) => arguments.push(argument),
+                    Err(m) => return Err(m),
+                };
+                if !self.match_token(&vec![Token_Type::COMMA]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after arguments.") {
+            Ok(paren) => paren.clone().into_owned(),
+            Err(err) => return Err(err),
+        };
+        Ok(Box::new(Call::new(callee, paren, arguments)))
     }
 
-    // → NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")"
-    fn primary(&mut self) -> Result<Box<dyn Expression>, String> {
+    // → NUMBER | STRING | "true" | "false" | "nil" | "this" | "(" expression ")" | IDENTIFIER
+    //   | "super" "." IDENTIFIER | interpolation
+    fn primary(&mut self) -> Result<Box<dyn Expression>, LoxError> {
         if self.match_token(&vec![Token_Type::TRUE]) {
-            return Ok(Box::new(Literal::new(Box::new(true), LiteralEnum::BOOLEAN)));
+            return Ok(Box::new(Literal::new(Value::Bool(true))));
         }
         if self.match_token(&vec![Token_Type::FALSE]) {
-            return Ok(Box::new(Literal::new(
-                Box::new(false),
-                LiteralEnum::BOOLEAN,
-            )));
+            return Ok(Box::new(Literal::new(Value::Bool(false))));
         }
         if self.match_token(&vec![Token_Type::NIL]) {
-            return Ok(Box::new(Literal::new(Box::new(0), LiteralEnum::NIL)));
+            return Ok(Box::new(Literal::new(Value::Nil)));
         }
         if self.match_token(&vec![Token_Type::NUMBER]) {
-            return Ok(Box::new(Literal::new(
-                Box::new(
-                    self.previous()
-                        .literal
-                        .parse::<f64>()
-                        .expect("Failed to convert string to number"),
-                ),
-                LiteralEnum::NUMBER,
-            )));
+            // The scanner already turned the literal into a plain number, this
+            // only fails for tokens that didn't come from it
+            return match self.previous().literal.parse::<f64>() {
+                Ok(number) => Ok(Box::new(Literal::new(Value::Number(number)))),
+                Err(_) => Err(self.parser_error(self.previous(), "Invalid number literal.")),
+            };
         }
         if self.match_token(&vec![Token_Type::STRING]) {
-            return Ok(Box::new(Literal::new(
-                Box::new(self.previous().literal.clone()),
-                LiteralEnum::STRING,
-            )));
+            return Ok(Box::new(Literal::new(Value::string(
+                &self.previous().literal,
+            ))));
+        }
+
+        if self.match_token(&vec![Token_Type::INTERPOLATION]) {
+            return self.interpolation();
+        }
+        if self.match_token(&vec![Token_Type::SUPER]) {
+            let keyword = self.previous().clone().into_owned();
+            match self.consume(&Token_Type::DOT, "Expect '.' after 'super'.") {
+                Ok(_) => {}
+                Err(err) => return Err(err),
+            };
+            let method = match self.consume(&Token_Type::IDENTIFIER, "Expect superclass method name.") {
+                Ok(method) => method.clone().into_owned(),
+                Err(err) => return Err(err),
+            };
+            return Ok(Box::new(Super::new(keyword, method)));
+        }
+        if self.match_token(&vec![Token_Type::THIS]) {
+            return Ok(Box::new(This::new(self.previous().clone().into_owned())));
+        }
+        if self.match_token(&vec![Token_Type::IDENTIFIER]) {
+            return Ok(Box::new(Variable::new(self.previous().clone().into_owned())));
         }
 
         // "(" expression ")"
         if self.match_token(&vec![Token_Type::LEFT_PAREN]) {
-            let expr = match self.expression() {
-                Ok(expr) => expr,
-                Err(m) => return Err(m),
-            };
+            let expr = self.expression()?;
             match self.consume(&Token_Type::RIGHT_PAREN, "Expect ')' after expression") {
                 Ok(_) => {}
                 Err(err) => return Err(err),
@@ -166,6 +643,37 @@ impl<'a> Parser<'a> {
         Err(self.parser_error(self.peek(), "Expected expression"))
     }
 
+    // interpolation  → INTERPOLATION expression ( INTERPOLATION expression )* STRING ;
+    // The scanner turns the } closing an embedded expression into the start
+    // of the next INTERPOLATION or of the final STRING
+    fn interpolation(&mut self) -> Result<Box<dyn Expression>, LoxError> {
+        let mut strings: Vec<String> = vec![self.previous().literal.to_string()];
+        let mut expressions: Vec<Box<dyn Expression>> = Vec::new();
+        loop {
+            // With nothing between the braces the next token is the rest of
+            // the string, which would otherwise parse as a string literal
+            let next = self.peek();
+            let is_rest_of_string = next.lexem.starts_with('}')
+                && (next.token_type == Token_Type::STRING || next.token_type == Token_Type::INTERPOLATION);
+            if is_rest_of_string {
+                return Err(self.parser_error(next, "Expect expression inside '${}'."));
+            }
+            match self.expression() {
+                Ok(expression) => expressions.push(expression),
+                Err(m) => return Err(m),
+            };
+            if self.match_token(&vec![Token_Type::INTERPOLATION]) {
+                strings.push(self.previous().literal.to_string());
+                continue;
+            }
+            match self.consume(&Token_Type::STRING, "Expect '}' after interpolated expression.") {
+                Ok(end) => strings.push(end.literal.to_string()),
+                Err(err) => return Err(err),
+            };
+            return Ok(Box::new(Interpolation::new(strings, expressions)));
+        }
+    }
+
     fn match_token(&mut self, token_types: &Vec<Token_Type>) -> bool {
         for token_type in token_types {
             // We look at the current token, if it matches, then we advance by 1
@@ -181,83 +689,125 @@ impl<'a> Parser<'a> {
         if self.is_at_end() {
             return false;
         }
-        return &self.peek().token_type == token_type;
+        &self.peek().token_type == token_type
     }
 
     // Advances the position by 1 and returns the token it was at when called
-    fn advance(&mut self) -> &Token {
-        if !self.is_at_end() {
-            self.current += 1
+    fn advance(&mut self) -> &Token<'a> {
+        if self.is_at_end() {
+            self.previous = self.current.clone();
+        } else {
+            let next = self.next_token();
+            self.previous = std::mem::replace(&mut self.current, next);
+        }
+        self.skip_error_tokens();
+        self.previous()
+    }
+
+    // Past the EOF there is only more EOF
+    fn next_token(&mut self) -> Token<'a> {
+        match self.tokens.next() {
+            Some(token) => token,
+            None => self.current.clone(),
         }
-        return self.previous();
     }
 
     fn is_at_end(&self) -> bool {
-        return self.peek().token_type == Token_Type::EOF;
+        self.peek().token_type == Token_Type::EOF
     }
 
-    fn peek(&self) -> &Token {
-        return self.tokens.get(self.current).unwrap();
+    fn peek(&self) -> &Token<'a> {
+        &self.current
     }
 
-    fn previous(&self) -> &Token {
-        return self.tokens.get(self.current - 1).unwrap();
+    fn previous(&self) -> &Token<'a> {
+        &self.previous
     }
 
-    fn consume(&mut self, expected: &Token_Type, error_message: &str) -> Result<&Token, String> {
+    fn consume(&mut self, expected: &Token_Type, error_message: &str) -> Result<&Token<'a>, LoxError> {
         if self.check(expected) {
             return Ok(self.advance());
         }
         Err(self.parser_error(self.peek(), error_message))
     }
 
-    fn parser_error(&self, token: &Token, message: &str) -> String {
-        Lox::error_token(token, message);
-        message.to_string()
-    }
-
-    // From chapter 3, not set up currently
-    // private void synchronize() {
-    //   advance();
-    //
-    //   while (!isAtEnd()) {
-    //     if (previous().type == SEMICOLON) return;
-    //
-    //     switch (peek().type) {
-    //       case CLASS:
-    //       case FUN:
-    //       case VAR:
-    //       case FOR:
-    //       case IF:
-    //       case WHILE:
-    //       case PRINT:
-    //       case RETURN:
-    //         return;
-    //     }
-    //
-    //     advance();
-    //   }
-    // }
+    fn parser_error(&self, token: &Token, message: &str) -> LoxError {
+        LoxError::parse(token, message)
+    }
+
+    // The scanner hands over what it couldn't scan as ERROR tokens. They are
+    // reported here and then never seen by the grammar, so current always
+    // points at a real token.
+    fn skip_error_tokens(&mut self) {
+        while self.peek().token_type == Token_Type::ERROR {
+            let token = self.peek();
+            self.errors.push(LoxError::scan(token.span, &token.literal));
+            self.scan_errors += 1;
+            self.current = self.next_token();
+        }
+    }
+
+    // Skips tokens until the start of what looks like the next statement,
+    // either just after a ';' or at a keyword that begins one
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type == Token_Type::SEMICOLON {
+                return;
+            }
+
+            match self.peek().token_type {
+                Token_Type::CLASS
+                | Token_Type::FUN
+                | Token_Type::VAR
+                | Token_Type::FOR
+                | Token_Type::IF
+                | Token_Type::WHILE
+                | Token_Type::PRINT
+                | Token_Type::RETURN => return,
+                _ => {}
+            }
+
+            self.advance();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Parser;
+    use crate::token::{token::Token, token_type::Token_Type};
     use crate::{expressions::printer::Printer, scanner::scanner::Scanner};
 
-    fn compare_code_to_expression(code: &str, expected: &str) {
-        let mut scanner = Scanner::new(&code.to_string());
-        let tokens = scanner.scan_tokens().ok().unwrap();
-        let mut parser = Parser::new(&tokens);
+    fn compare_code_to_program(code: &str, expected: &str) {
+        let mut parser = Parser::new(Scanner::new(code));
         let printer = Printer {};
-        assert_eq!(printer.convert(parser.parse().ok().unwrap()), expected);
+        assert_eq!(printer.convert_program(&parser.parse().ok().unwrap()), expected);
+    }
+
+    // Wraps the expression in an expression statement
+    fn compare_code_to_expression(code: &str, expected: &str) {
+        compare_code_to_program(&format!("{};", code), &format!("(; {})", expected));
     }
 
     fn compare_code_to_err(code: &str, expected: &str) {
-        let mut scanner = Scanner::new(&code.to_string());
-        let tokens = scanner.scan_tokens().ok().unwrap();
-        let mut parser = Parser::new(&tokens);
-        assert_eq!(parser.parse().err().unwrap(), expected);
+        let mut parser = Parser::new(Scanner::new(code));
+        let errors = parser.parse().err().unwrap();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].to_string(), expected);
+    }
+
+    fn compare_code_to_errs(code: &str, expected: &[&str]) {
+        let mut parser = Parser::new(Scanner::new(code));
+        let errors: Vec<String> = parser
+            .parse()
+            .err()
+            .unwrap()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(errors, expected);
     }
 
     #[test]
@@ -307,7 +857,241 @@ mod tests {
 
     #[test]
     fn test_all_error_parser() {
-        compare_code_to_err("( 4", "Expect ')' after expression");
-        compare_code_to_err("", "Expected expression");
+        compare_code_to_err("( 4;", "[line 1] Error at ';': Expect ')' after expression");
+        compare_code_to_err("4 * * 3;", "[line 1] Error at '*': Expected expression");
+        compare_code_to_err("print 4", "[line 1] Error at end: Expect ';' after value.");
+        compare_code_to_err("4 4;", "[line 1] Error at '4': Expect ';' after expression.");
+        compare_code_to_err("{ print 4;", "[line 1] Error at end: Expect '}' after block.");
+        compare_code_to_err("var 4 = 3;", "[line 1] Error at '4': Expect variable name.");
+        compare_code_to_err("var a = 3", "[line 1] Error at end: Expect ';' after variable declaration.");
+        compare_code_to_err("1 = 2;", "[line 1] Error at '=': Invalid assignment target.");
+        compare_code_to_err("a + b = 2;", "[line 1] Error at '=': Invalid assignment target.");
+        compare_code_to_err("(a) = 2;", "[line 1] Error at '=': Invalid assignment target.");
+    }
+
+    #[test]
+    fn test_control_flow_parser() {
+        compare_code_to_expression("a or b and c", "(or a (and b c))");
+        compare_code_to_expression("a and b or c", "(or (and a b) c)");
+        compare_code_to_expression("a = b or c", "(= a (or b c))");
+        compare_code_to_program("if (a) print 1;", "(if a (print 1))");
+        compare_code_to_program("if (a) print 1; else print 2;", "(if a (print 1) (print 2))");
+        compare_code_to_program(
+            "if (a) if (b) print 1; else print 2;",
+            "(if a (if b (print 1) (print 2)))",
+        );
+        compare_code_to_program("while (a < 3) a = a + 1;", "(while (< a 3) (; (= a (+ a 1))))");
+        compare_code_to_program(
+            "for (var i = 0; i < 3; i = i + 1) print i;",
+            "(block (var i 0) (while (< i 3) (block (print i) (; (= i (+ i 1))))))",
+        );
+        compare_code_to_program("for (;;) print 1;", "(while true (print 1))");
+        compare_code_to_err("if a) print 1;", "[line 1] Error at 'a': Expect '(' after 'if'.");
+        compare_code_to_err("while (a print 1;", "[line 1] Error at 'print': Expect ')' after condition.");
+        compare_code_to_err("for (;; i = i + 1 print 1;", "[line 1] Error at 'print': Expect ')' after for clauses.");
+    }
+
+    #[test]
+    fn test_functions_parser() {
+        compare_code_to_expression("f()", "(call f)");
+        compare_code_to_expression("f(1, a + 2)", "(call f 1 (+ a 2))");
+        compare_code_to_expression("f(1)(2)", "(call (call f 1) 2)");
+        compare_code_to_expression("-f()", "(- (call f))");
+        compare_code_to_program("fun f() {}", "(fun f ())");
+        compare_code_to_program(
+            "fun add(a, b) { return a + b; }",
+            "(fun add (a b) (return (+ a b)))",
+        );
+        compare_code_to_program("fun f() { return; }", "(fun f () (return))");
+        compare_code_to_err("f(1, 2;", "[line 1] Error at ';': Expect ')' after arguments.");
+        compare_code_to_err("fun (a) {}", "[line 1] Error at '(': Expect function name.");
+        compare_code_to_err("fun f(a, 1) {}", "[line 1] Error at '1': Expect parameter name.");
+        compare_code_to_err("fun f(a) print a;", "[line 1] Error at 'print': Expect '{' before function body.");
+        compare_code_to_err("return 1", "[line 1] Error at end: Expect ';' after return value.");
+
+        let arguments = vec!["1"; 256].join(", ");
+        compare_code_to_err(
+            &format!("f({});", arguments),
+            "[line 1] Error at '1': Can't have more than 255 arguments.",
+        );
+        // Too many parameters doesn't derail the parser, it carries on
+        let params = vec!["a"; 256].join(", ");
+        compare_code_to_errs(
+            &format!("fun f({}) {{}} print ;", params),
+            &[
+                "[line 1] Error at 'a': Can't have more than 255 parameters.",
+                "[line 1] Error at ';': Expected expression",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_classes_parser() {
+        compare_code_to_program("class A {}", "(class A)");
+        compare_code_to_program(
+            "class A { init(a) { this.a = a; } get() { return this.a; } }",
+            "(class A (fun init (a) (; (set this a a))) (fun get () (return (get this a))))",
+        );
+        compare_code_to_expression("a.b.c", "(get (get a b) c)");
+        compare_code_to_expression("a.b(1).c", "(get (call (get a b) 1) c)");
+        compare_code_to_expression("a.b.c = 3", "(set (get a b) c 3)");
+        compare_code_to_expression("a().b = c = 1", "(set (call a) b (= c 1))");
+        compare_code_to_err("class {}", "[line 1] Error at '{': Expect class name.");
+        compare_code_to_err("class A { fun f() {} }", "[line 1] Error at 'fun': Expect method name.");
+        compare_code_to_err("class A { f() {}", "[line 1] Error at end: Expect '}' after class body.");
+        compare_code_to_err("a.1;", "[line 1] Error at '1': Expect property name after '.'.");
+        compare_code_to_err("a.b() = 1;", "[line 1] Error at '=': Invalid assignment target.");
+    }
+
+    #[test]
+    fn test_error_recovery_parser() {
+        compare_code_to_errs(
+            "var = 1;\nprint 1\nprint 2;\nfun (a) {}\nprint (1 + ;",
+            &[
+                "[line 1] Error at '=': Expect variable name.",
+                "[line 3] Error at 'print': Expect ';' after value.",
+                "[line 4] Error at '(': Expect function name.",
+                "[line 5] Error at ';': Expected expression",
+            ],
+        );
+        // Errors inside a block are recovered from inside that block
+        compare_code_to_errs(
+            "{ var a = ; print a; }\nprint 1 + ;",
+            &[
+                "[line 1] Error at ';': Expected expression",
+                "[line 2] Error at ';': Expected expression",
+            ],
+        );
+        // Synchronizing stops at the keyword starting the next statement
+        compare_code_to_errs(
+            "1 + 2 3 4 if (true) print 1 while (x) {}",
+            &[
+                "[line 1] Error at '3': Expect ';' after expression.",
+                "[line 1] Error at 'while': Expect ';' after value.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_scanner_errors_parser() {
+        compare_code_to_errs("print 1 @ 2;", &["[line 1] Error: Unknown char @ unable to be scanned"]);
+        compare_code_to_errs(
+            "var a = #;\nprint a;",
+            &["[line 1] Error: Unknown char # unable to be scanned"],
+        );
+        compare_code_to_errs(
+            "print \"never closed;",
+            &["[line 1] Error: Missing closing \" on string token"],
+        );
+        compare_code_to_errs("@ print 1;", &["[line 1] Error: Unknown char @ unable to be scanned"]);
+        // Syntax errors in other statements are still reported, in source order
+        compare_code_to_errs(
+            "print ;\nvar a = @;\nprint 1 +;",
+            &[
+                "[line 1] Error at ';': Expected expression",
+                "[line 2] Error: Unknown char @ unable to be scanned",
+                "[line 3] Error at ';': Expected expression",
+            ],
+        );
+        compare_code_to_errs(
+            "fun f() { @ print 1 }",
+            &[
+                "[line 1] Error: Unknown char @ unable to be scanned",
+                "[line 1] Error at '}': Expect ';' after value.",
+            ],
+        );
+        // Parses fine once the bad char is skipped, but the error still counts
+        compare_code_to_errs("print 1 + 2 @;", &["[line 1] Error: Unknown char @ unable to be scanned"]);
+    }
+
+    #[test]
+    fn test_number_literals_parser() {
+        compare_code_to_expression("0xFF + 1_000 * 2.5e-1", "(+ 255 (* 1000 0.25))");
+        compare_code_to_errs("print 0x;", &["[line 1] Error: Missing digits after 0x in hex literal"]);
+
+        // A NUMBER token not made by the scanner is an error rather than a panic
+        let tokens = vec![
+            Token::new(Token_Type::NUMBER, "1x".to_string(), "1x".to_string(), 1),
+            Token::new(Token_Type::SEMICOLON, ";".to_string(), "".to_string(), 1),
+            Token::new(Token_Type::EOF, "".to_string(), "".to_string(), 1),
+        ];
+        let errors = Parser::new(tokens.into_iter()).parse().err().unwrap();
+        assert_eq!(errors[0].to_string(), "[line 1] Error at '1x': Invalid number literal.");
+    }
+
+    #[test]
+    fn test_doc_comments_parser() {
+        let code = "/// A point.\n/// Has x and y.\nclass Point {\n  /// Makes one.\n  init() {}\n  plain() {}\n}\n\
+                    /// The answer.\nvar answer = 42;\n/// Adds.\nfun add(a, b) { return a + b; }\n\
+                    print answer;";
+        let mut parser = Parser::new(Scanner::new(code));
+        let statements = parser.parse().ok().unwrap();
+        let docs: Vec<Option<&str>> = statements.iter().map(|statement| statement.doc()).collect();
+        assert_eq!(
+            docs,
+            vec![Some("A point.\nHas x and y."), Some("The answer."), Some("Adds."), None]
+        );
+    }
+
+    #[test]
+    fn test_interpolation_parser() {
+        compare_code_to_expression(
+            "\"Hello ${name}, you are ${age + 1}\"",
+            "(interpolate \"Hello \" name \", you are \" (+ age 1))",
+        );
+        compare_code_to_expression("\"${a}${b}\"", "(interpolate a b)");
+        compare_code_to_expression("\"${\"in ${x}\"}!\"", "(interpolate (interpolate \"in \" x) \"!\")");
+        compare_code_to_expression("\"${a}\" + \"b\"", "(+ (interpolate a) b)");
+        compare_code_to_err("\"${}\";", "[line 1] Error at '}\"': Expect expression inside '${}'.");
+        compare_code_to_err("\"${a}${}\";", "[line 1] Error at '}\"': Expect expression inside '${}'.");
+        compare_code_to_err(
+            "\"${a b}\";",
+            "[line 1] Error at 'b': Expect '}' after interpolated expression.",
+        );
+        // The scanner's error is the only one reported
+        compare_code_to_errs(
+            "print \"${a\n",
+            &["[line 2] Error: Missing closing } on string interpolation"],
+        );
+    }
+
+    #[test]
+    fn test_inheritance_parser() {
+        compare_code_to_program("class B < A {}", "(class B < A)");
+        compare_code_to_program(
+            "class B < A { m() { return super.m(); } }",
+            "(class B < A (fun m () (return (call (super m)))))",
+        );
+        compare_code_to_program(
+            "class B < A { m() { fun f() { super.m; } } }",
+            "(class B < A (fun m () (fun f () (; (super m)))))",
+        );
+        // Whether the classes make sense is left to the resolver
+        compare_code_to_program("class A < A {}", "(class A < A)");
+        compare_code_to_program("super.m();", "(; (call (super m)))");
+        compare_code_to_err("class A < 1 {}", "[line 1] Error at '1': Expect superclass name.");
+        compare_code_to_err("class A < \"B\" {}", "[line 1] Error at '\"B\"': Expect superclass name.");
+        compare_code_to_err("class B < A { m() { super; } }", "[line 1] Error at ';': Expect '.' after 'super'.");
+    }
+
+    #[test]
+    fn test_variables_parser() {
+        compare_code_to_program("var a;", "(var a)");
+        compare_code_to_program("var a = 1 + 2;", "(var a (+ 1 2))");
+        compare_code_to_expression("a", "a");
+        compare_code_to_expression("a = 3", "(= a 3)");
+        compare_code_to_expression("a = b = c", "(= a (= b c))");
+        compare_code_to_program("{ var a = 1; a = a * 2; }", "(block (var a 1) (; (= a (* a 2))))");
+    }
+
+    #[test]
+    fn test_statements_parser() {
+        compare_code_to_program("", "");
+        compare_code_to_program("print 1 + 2;", "(print (+ 1 2))");
+        compare_code_to_program("1; print \"a\";", "(; 1)\n(print a)");
+        compare_code_to_program(
+            "{ print 1; { 2; } } print 3;",
+            "(block (print 1) (block (; 2)))\n(print 3)",
+        );
     }
 }
@@ -0,0 +1,463 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use crate::{
+    error::error::LoxError,
+    expressions::{
+        assign::Assign, binary::Binary, call::Call, expression::Expression, get::Get,
+        grouping::Grouping, interpolation::Interpolation, literal::Literal, logical::Logical,
+        set::Set, super_expression::Super, this::This, unary::Unary, variable::Variable,
+        visitor::Visitor,
+    },
+    statements::{
+        block::Block, class::Class, expression_statement::ExpressionStatement, function::Function,
+        if_statement::If, print::Print, return_statement::Return, statement::Statement, var::Var,
+        visitor::Visitor as StatementVisitor, while_statement::While,
+    },
+    token::token::Token,
+};
+
+// What kind of function body we are in, to validate return statements
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+// What kind of class body we are in, to validate this and super
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+// What a name in a scope was declared as, so a superclass naming a local
+// variable or function is caught before anything runs
+#[derive(Clone, Copy, PartialEq)]
+enum DeclarationKind {
+    Variable,
+    Function,
+    Class,
+}
+
+struct Binding {
+    kind: DeclarationKind,
+    // False while the initializer is being resolved
+    ready: bool,
+}
+
+// Walks the tree once before it runs, telling every local variable reference
+// how many scopes out its binding lives and reporting the errors that can be
+// found without running anything. Globals are left unresolved.
+pub struct Resolver {
+    // Innermost scope last
+    scopes: RefCell<Vec<HashMap<String, Binding>>>,
+    current_function: Cell<FunctionType>,
+    current_class: Cell<ClassType>,
+    errors: RefCell<Vec<LoxError>>,
+}
+
+impl Default for Resolver {
+    fn default() -> Resolver {
+        Resolver::new()
+    }
+}
+
+#[allow(dead_code)]
+impl Resolver {
+    pub fn new() -> Resolver {
+        Resolver {
+            scopes: RefCell::new(Vec::new()),
+            current_function: Cell::new(FunctionType::None),
+            current_class: Cell::new(ClassType::None),
+            errors: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Resolving keeps going after an error so all of them get reported
+    pub fn resolve(&self, statements: &[Box<dyn Statement>]) -> Result<(), Vec<LoxError>> {
+        self.resolve_statements(statements);
+        let errors = self.errors.take();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(())
+    }
+
+    fn resolve_statements(&self, statements: &[Box<dyn Statement>]) {
+        for statement in statements {
+            statement.accept(self);
+        }
+    }
+
+    fn resolve_expression(&self, expression: &dyn Expression) {
+        expression.accept(self);
+    }
+
+    fn resolve_function(&self, function: &Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function.replace(function_type);
+        // Parameters and the body share one scope, just like a call does
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param, DeclarationKind::Variable);
+            self.define(param);
+        }
+        self.resolve_statements(&function.body);
+        self.end_scope();
+        self.current_function.set(enclosing_function);
+    }
+
+    // Counts the scopes between the reference and its declaration. Not found
+    // means global, which also clears whatever an earlier pass left behind
+    fn resolve_local(&self, depth: &Cell<Option<usize>>, name: &Token) {
+        let scopes = self.scopes.borrow();
+        for (distance, scope) in scopes.iter().rev().enumerate() {
+            if scope.contains_key(name.lexem.as_ref()) {
+                depth.set(Some(distance));
+                return;
+            }
+        }
+        depth.set(None);
+    }
+
+    fn begin_scope(&self) {
+        self.scopes.borrow_mut().push(HashMap::new());
+    }
+
+    fn end_scope(&self) {
+        self.scopes.borrow_mut().pop();
+    }
+
+    // Adds the name to the innermost scope but marks it as not ready yet
+    fn declare(&self, name: &Token, kind: DeclarationKind) {
+        let mut scopes = self.scopes.borrow_mut();
+        let scope = match scopes.last_mut() {
+            Some(scope) => scope,
+            None => return,
+        };
+        if scope.contains_key(name.lexem.as_ref()) {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+        scope.insert(name.lexem.to_string(), Binding { kind, ready: false });
+    }
+
+    fn define(&self, name: &Token) {
+        let mut scopes = self.scopes.borrow_mut();
+        if let Some(binding) = scopes.last_mut().and_then(|scope| scope.get_mut(name.lexem.as_ref())) {
+            binding.ready = true;
+        }
+    }
+
+    // What the innermost local with this name was declared as, None for globals
+    fn declaration_kind(&self, name: &Token) -> Option<DeclarationKind> {
+        let scopes = self.scopes.borrow();
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.lexem.as_ref()))
+            .map(|binding| binding.kind)
+    }
+
+    // For the names the interpreter binds itself, "this" and "super"
+    fn define_keyword(&self, name: &str) {
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.insert(name.to_string(), Binding { kind: DeclarationKind::Variable, ready: true });
+        }
+    }
+
+    fn error(&self, token: &Token, message: &str) {
+        self.errors.borrow_mut().push(LoxError::resolve(token, message));
+    }
+}
+
+impl StatementVisitor<()> for Resolver {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) {
+        self.resolve_expression(element.expression.as_ref());
+    }
+
+    fn visit_print(&self, element: &Print) {
+        self.resolve_expression(element.expression.as_ref());
+    }
+
+    fn visit_block(&self, element: &Block) {
+        self.begin_scope();
+        self.resolve_statements(&element.statements);
+        self.end_scope();
+    }
+
+    // Declared before and defined after the initializer, so the initializer
+    // can tell it is reading the variable it is initializing
+    fn visit_var(&self, element: &Var) {
+        self.declare(&element.name, DeclarationKind::Variable);
+        if let Some(initializer) = &element.initializer {
+            self.resolve_expression(initializer.as_ref());
+        }
+        self.define(&element.name);
+    }
+
+    fn visit_if(&self, element: &If) {
+        self.resolve_expression(element.condition.as_ref());
+        element.then_branch.accept(self);
+        if let Some(else_branch) = &element.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_while(&self, element: &While) {
+        self.resolve_expression(element.condition.as_ref());
+        element.body.accept(self);
+    }
+
+    // The name is defined right away so the function can call itself
+    fn visit_function(&self, element: &Function) {
+        self.declare(&element.name, DeclarationKind::Function);
+        self.define(&element.name);
+        self.resolve_function(element, FunctionType::Function);
+    }
+
+    fn visit_return(&self, element: &Return) {
+        if self.current_function.get() == FunctionType::None {
+            self.error(&element.keyword, "Can't return from top-level code.");
+        }
+        if let Some(value) = &element.value {
+            if self.current_function.get() == FunctionType::Initializer {
+                self.error(&element.keyword, "Can't return a value from an initializer.");
+            }
+            self.resolve_expression(value.as_ref());
+        }
+    }
+
+    // Mirrors the scopes the interpreter builds around methods: one holding
+    // "super" for subclasses, then the one a bound method holds "this" in
+    fn visit_class(&self, element: &Class) {
+        let enclosing_class = self.current_class.replace(ClassType::Class);
+        self.declare(&element.name, DeclarationKind::Class);
+        self.define(&element.name);
+
+        if let Some(superclass) = &element.superclass {
+            if superclass.name.lexem == element.name.lexem {
+                self.error(&superclass.name, "A class can't inherit from itself.");
+            }
+            // Only locals are known here, a global one is checked when it runs
+            match self.declaration_kind(&superclass.name) {
+                Some(DeclarationKind::Variable | DeclarationKind::Function) => {
+                    self.error(&superclass.name, "Superclass must be a class.")
+                }
+                Some(DeclarationKind::Class) | None => {}
+            }
+            self.current_class.set(ClassType::Subclass);
+            self.visit_variable(superclass);
+
+            self.begin_scope();
+            self.define_keyword("super");
+        }
+
+        self.begin_scope();
+        self.define_keyword("this");
+        for method in &element.methods {
+            let function_type = match method.name.lexem.as_ref() {
+                "init" => FunctionType::Initializer,
+                _ => FunctionType::Method,
+            };
+            self.resolve_function(method, function_type);
+        }
+        self.end_scope();
+
+        if element.superclass.is_some() {
+            self.end_scope();
+        }
+        self.current_class.set(enclosing_class);
+    }
+}
+
+impl Visitor<()> for Resolver {
+    fn visit_binary(&self, element: &Binary) {
+        self.resolve_expression(element.left.as_ref());
+        self.resolve_expression(element.right.as_ref());
+    }
+
+    fn visit_literal(&self, _element: &Literal) {}
+
+    fn visit_unary(&self, element: &Unary) {
+        self.resolve_expression(element.right.as_ref());
+    }
+
+    fn visit_grouping(&self, element: &Grouping) {
+        self.resolve_expression(element.exp.as_ref());
+    }
+
+    fn visit_variable(&self, element: &Variable) {
+        let in_own_initializer = match self.scopes.borrow().last() {
+            Some(scope) => matches!(scope.get(element.name.lexem.as_ref()), Some(binding) if !binding.ready),
+            None => false,
+        };
+        if in_own_initializer {
+            self.error(&element.name, "Can't read local variable in its own initializer.");
+        }
+        self.resolve_local(&element.depth, &element.name);
+    }
+
+    fn visit_assign(&self, element: &Assign) {
+        self.resolve_expression(element.value.as_ref());
+        self.resolve_local(&element.depth, &element.name);
+    }
+
+    fn visit_logical(&self, element: &Logical) {
+        self.resolve_expression(element.left.as_ref());
+        self.resolve_expression(element.right.as_ref());
+    }
+
+    fn visit_call(&self, element: &Call) {
+        self.resolve_expression(element.callee.as_ref());
+        for argument in &element.arguments {
+            self.resolve_expression(argument.as_ref());
+        }
+    }
+
+    // Properties are looked up dynamically, only the object gets resolved
+    fn visit_get(&self, element: &Get) {
+        self.resolve_expression(element.object.as_ref());
+    }
+
+    fn visit_set(&self, element: &Set) {
+        self.resolve_expression(element.value.as_ref());
+        self.resolve_expression(element.object.as_ref());
+    }
+
+    fn visit_this(&self, element: &This) {
+        if self.current_class.get() == ClassType::None {
+            self.error(&element.keyword, "Can't use 'this' outside of a class.");
+            return;
+        }
+        self.resolve_local(&element.depth, &element.keyword);
+    }
+
+    fn visit_interpolation(&self, element: &Interpolation) {
+        for expression in &element.expressions {
+            self.resolve_expression(expression.as_ref());
+        }
+    }
+
+    fn visit_super(&self, element: &Super) {
+        match self.current_class.get() {
+            ClassType::None => {
+                self.error(&element.keyword, "Can't use 'super' outside of a class.")
+            }
+            ClassType::Class => self.error(
+                &element.keyword,
+                "Can't use 'super' in a class with no superclass.",
+            ),
+            ClassType::Subclass => self.resolve_local(&element.depth, &element.keyword),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resolver;
+    use crate::{parser::parser::Parser, scanner::scanner::Scanner};
+
+    fn resolve_code(code: &str) -> Result<(), Vec<String>> {
+        let mut parser = Parser::new(Scanner::new(code));
+        let statements = parser.parse().ok().unwrap();
+        let resolver = Resolver::new();
+        resolver
+            .resolve(&statements)
+            .map_err(|errors| errors.iter().map(|error| error.to_string()).collect())
+    }
+
+    fn compare_code_to_errs(code: &str, expected: &[&str]) {
+        assert_eq!(resolve_code(code).err().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_resolver_success() {
+        let codes = [
+            "var a = 1; var a = a + 1;",
+            "var a = 1; { var b = a; { var a = b; } }",
+            "fun f(a) { var b = a; return b; }",
+            "fun f() { fun g() { return f; } return g; }",
+            "class A { init() { this.a = 1; return; } m() { return this.a; } }",
+            "class A {} class B < A { m() { fun f() { return super.m; } } }",
+        ];
+        for code in codes {
+            assert_eq!(resolve_code(code), Ok(()), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_resolver_errors() {
+        compare_code_to_errs(
+            "{ var a = 1; { var a = a; } }",
+            &["[line 1] Error at 'a': Can't read local variable in its own initializer."],
+        );
+        compare_code_to_errs(
+            "{ var a = 1; var a = 2; }",
+            &["[line 1] Error at 'a': Already a variable with this name in this scope."],
+        );
+        compare_code_to_errs(
+            "fun f(a, a) {}",
+            &["[line 1] Error at 'a': Already a variable with this name in this scope."],
+        );
+        compare_code_to_errs("return 1;", &["[line 1] Error at 'return': Can't return from top-level code."]);
+        compare_code_to_errs(
+            "class A { init() { return 1; } }",
+            &["[line 1] Error at 'return': Can't return a value from an initializer."],
+        );
+        compare_code_to_errs("print this;", &["[line 1] Error at 'this': Can't use 'this' outside of a class."]);
+        compare_code_to_errs(
+            "fun f() { return this; }",
+            &["[line 1] Error at 'this': Can't use 'this' outside of a class."],
+        );
+    }
+
+    #[test]
+    fn test_resolver_class_errors() {
+        compare_code_to_errs("class A < A {}", &["[line 1] Error at 'A': A class can't inherit from itself."]);
+        compare_code_to_errs(
+            "{ var A = 1; class B < A {} }",
+            &["[line 1] Error at 'A': Superclass must be a class."],
+        );
+        compare_code_to_errs(
+            "fun f() { fun A() {} class B < A {} }",
+            &["[line 1] Error at 'A': Superclass must be a class."],
+        );
+        // A local class is fine, and a global is left to the interpreter
+        assert!(resolve_code("{ class A {} class B < A {} }").is_ok());
+        assert!(resolve_code("var A = 1; class B < A {}").is_ok());
+        compare_code_to_errs(
+            "super.m();",
+            &["[line 1] Error at 'super': Can't use 'super' outside of a class."],
+        );
+        compare_code_to_errs(
+            "class A { m() { super.m(); } }",
+            &["[line 1] Error at 'super': Can't use 'super' in a class with no superclass."],
+        );
+        // The context is restored once a nested class body ends
+        compare_code_to_errs(
+            "class B < A { m() { class C { n() { super.n(); } } } }",
+            &["[line 1] Error at 'super': Can't use 'super' in a class with no superclass."],
+        );
+        compare_code_to_errs(
+            "class B < A {} super.m();",
+            &["[line 1] Error at 'super': Can't use 'super' outside of a class."],
+        );
+    }
+
+    #[test]
+    fn test_resolver_reports_every_error() {
+        compare_code_to_errs(
+            "return;\nprint this;\n{ var a; var a; }",
+            &[
+                "[line 1] Error at 'return': Can't return from top-level code.",
+                "[line 2] Error at 'this': Can't use 'this' outside of a class.",
+                "[line 3] Error at 'a': Already a variable with this name in this scope.",
+            ],
+        );
+    }
+}
@@ -1,51 +1,124 @@
-use crate::lox::Lox;
+use std::borrow::Cow;
 
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_security::{is_potential_mixed_script_confusable_char, skeleton, MixedScript};
+
+use super::super::error::diagnostics::{Diagnostic, Severity};
+use super::super::token::span::Span;
 use super::super::token::token::Token;
 use super::super::token::token_type::Token_Type;
 
-pub struct Scanner {
-    source: Vec<char>,
+// Works straight on the source text, tokens borrow their lexem from it and
+// are only scanned when the parser asks for the next one
+pub struct Scanner<'a> {
+    source: &'a str,
+    // Byte offsets into source
     start: usize,
     current: usize,
     line: usize,
-    an_error_occured: bool,
+    // Chars consumed on the current line so far, to work out columns
+    column: usize,
+    // Line and column the token being scanned started at
+    start_line: usize,
+    start_column: usize,
+    // One entry per ${ we are inside of, counting the braces opened since
+    // so we know which } goes back to the string
+    interpolations: Vec<usize>,
+    // Lines of /// comments waiting for the next token to be attached to
+    docs: Vec<String>,
+    // Things that scan fine but look suspicious, for Lox to show
+    warnings: Vec<Diagnostic>,
+    // Set once the EOF token has been handed out
+    finished: bool,
 }
 
-impl Scanner {
-    pub fn new(source: &String) -> Scanner {
+// The EOF token is always the last one, after it the scanner is done
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.finished {
+            return None;
+        }
+        while !self.is_at_end() {
+            self.start_token();
+            if let Some(token) = self.scan_token() {
+                return Some(self.attach_docs(token));
+            }
+        }
+        self.start_token();
+        if !self.interpolations.is_empty() {
+            self.interpolations.clear();
+            return Some(self.error_token("Missing closing } on string interpolation"));
+        }
+        self.finished = true;
+        let eof = self.generate_token(Token_Type::EOF, None);
+        Some(self.attach_docs(eof))
+    }
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(source: &'a str) -> Scanner<'a> {
         Scanner {
-            source: source.chars().collect(),
+            source,
             start: 0,
             current: 0,
             line: 1,
-            an_error_occured: false,
+            column: 0,
+            start_line: 1,
+            start_column: 1,
+            interpolations: Vec::new(),
+            docs: Vec::new(),
+            warnings: Vec::new(),
+            finished: false,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Vec<Token>> {
-        let mut tokens: Vec<Token> = Vec::new();
-        while !self.is_at_end() {
-            self.start = self.current;
-            match self.scan_token() {
-                Some(token) => tokens.push(token),
-                None => continue,
-            }
-        }
-        tokens.push(self.generate_token(Token_Type::EOF, None));
-        if self.an_error_occured {
-            return Err(tokens);
+    // Never fails, anything that can't be scanned becomes an ERROR token
+    // for the parser to report. Scans everything that is left in one go.
+    pub fn scan_tokens(&mut self) -> Vec<Token<'a>> {
+        self.by_ref().collect()
+    }
+
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    // Doc comments are trivia of whatever token comes after them, which is
+    // how the parser finds the declaration they document
+    fn attach_docs(&mut self, mut token: Token<'a>) -> Token<'a> {
+        if !self.docs.is_empty() {
+            token.doc = Some(self.docs.join("\n"));
+            self.docs.clear();
         }
-        Ok(tokens)
+        token
     }
 
-    fn scan_token(&mut self) -> Option<Token> {
+    fn scan_token(&mut self) -> Option<Token<'a>> {
         let current_char: char = self.advance();
         match current_char {
             // Single use char
             '(' => Some(self.generate_token(Token_Type::LEFT_PAREN, None)),
             ')' => Some(self.generate_token(Token_Type::RIGHT_PAREN, None)),
-            '{' => Some(self.generate_token(Token_Type::LEFT_BRACE, None)),
-            '}' => Some(self.generate_token(Token_Type::RIGHT_BRACE, None)),
+            '{' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces += 1;
+                }
+                Some(self.generate_token(Token_Type::LEFT_BRACE, None))
+            }
+            '}' => match self.interpolations.last_mut() {
+                // Closes the ${, so the string picks up where it left off
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string()
+                }
+                Some(braces) => {
+                    *braces -= 1;
+                    Some(self.generate_token(Token_Type::RIGHT_BRACE, None))
+                }
+                None => Some(self.generate_token(Token_Type::RIGHT_BRACE, None)),
+            },
             ',' => Some(self.generate_token(Token_Type::COMMA, None)),
             '.' => Some(self.generate_token(Token_Type::DOT, None)),
             '-' => Some(self.generate_token(Token_Type::MINUS, None)),
@@ -72,11 +145,19 @@ impl Scanner {
             // Comments ( like this :) )
             '/' => {
                 if self.match_next(&'/') {
+                    // Exactly three slashes, //// is just a comment
+                    let is_doc = self.peek_next() == '/' && self.peek_double_next() != '/';
                     while self.peek_next() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    if is_doc {
+                        self.doc_comment();
+                    }
                     return None;
                 }
+                if self.match_next(&'*') {
+                    return self.block_comment();
+                }
                 Some(self.generate_token(Token_Type::SLASH, None))
             }
             // Ignore Whitespace
@@ -85,44 +166,72 @@ impl Scanner {
             '\t' => None,
             // Newline
             '\n' => {
-                self.line += 1;
+                self.new_line();
                 None
             }
             // Strings
             '"' => self.string(),
+            // Raw strings, r"C:\path" keeps every backslash as it is
+            'r' if self.peek_next() == '"' => {
+                self.advance();
+                self.raw_string()
+            }
             // Catch remainder
             misc => {
                 // Numbers
-                if misc.is_digit(10) {
-                    return Some(self.number());
+                if misc.is_ascii_digit() {
+                    return Some(self.number(misc));
                 }
                 // Reserved Key Words
-                if misc.is_alphabetic() || misc == '_' {
+                if is_xid_start(misc) || misc == '_' {
                     return Some(self.identifier());
                 }
-                self.scanner_error(format!("Unknown char {} unable to be scanned", misc).trim());
-                None
+                Some(self.error_token(format!("Unknown char {} unable to be scanned", misc).trim()))
             }
         }
     }
 
-    fn generate_token(&mut self, token_type: Token_Type, literal: Option<String>) -> Token {
+    fn generate_token(&mut self, token_type: Token_Type, literal: Option<Cow<'a, str>>) -> Token<'a> {
         if token_type == Token_Type::EOF {
-            return Token::new(token_type, "".to_string(), "".to_string(), self.line);
+            return Token::spanned(token_type, "", "", self.line, self.span());
         }
 
         let literal_ = match literal {
             Some(literal) => literal,
-            None => "".to_string(),
+            None => Cow::Borrowed(""),
         };
-        let lexem: String = self.source[self.start..self.current].iter().collect();
-        Token::new(token_type, lexem, literal_, self.line)
+        let lexem = self.text(self.start, self.current);
+        Token::spanned(token_type, lexem, literal_, self.line, self.span())
+    }
+
+    // The source between two byte offsets, borrowed for as long as the source is
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        let source: &'a str = self.source;
+        &source[start..end]
     }
 
-    // We grab the char at the current index and increment the current
+    fn start_token(&mut self) {
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column + 1;
+    }
+
+    // Covers everything scanned since start_token
+    fn span(&self) -> Span {
+        Span::new(self.start, self.current, self.start_line, self.start_column)
+    }
+
+    // Called once the '\n' has been consumed
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 0;
+    }
+
+    // We grab the char at the current index and move past it
     fn advance(&mut self) -> char {
-        let current_char: char = self.source.get(self.current).unwrap().clone();
-        self.current += 1;
+        let current_char: char = self.source[self.current..].chars().next().unwrap();
+        self.current += current_char.len_utf8();
+        self.column += 1;
         current_char
     }
 
@@ -132,66 +241,308 @@ impl Scanner {
         if self.is_at_end() {
             return false;
         }
-        let next_char = self.source.get(self.current).unwrap(); // We already incremented
-        if next_char != expected_match {
+        if self.peek_next() != *expected_match {
             return false;
         }
-        self.current += 1;
+        self.advance();
         true
     }
 
     fn peek_next(&mut self) -> char {
-        self.source.get(self.current).unwrap_or(&'\0').clone()
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_double_next(&mut self) -> char {
-        self.source.get(self.current + 1).unwrap_or(&'\0').clone()
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
-    fn string(&mut self) -> Option<Token> {
+    // Keeps the text after the ///, dropping one space so "/// Adds" gives "Adds"
+    fn doc_comment(&mut self) {
+        let text = self.text(self.start + 3, self.current).trim_end_matches('\r');
+        self.docs.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+    }
+
+    // Called once the /* has been consumed. Block comments nest, so every /*
+    // inside needs its own */ before the comment is over.
+    fn block_comment(&mut self) -> Option<Token<'a>> {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                return Some(self.error_token("Missing closing */ on block comment"));
+            }
+            match self.advance() {
+                '/' if self.peek_next() == '*' => {
+                    self.advance();
+                    depth += 1;
+                }
+                '*' if self.peek_next() == '/' => {
+                    self.advance();
+                    depth -= 1;
+                }
+                '\n' => self.new_line(),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    // The literal is the string with its escapes decoded. A bad escape doesn't
+    // end the string, the rest of it is still consumed so the error token
+    // points at just that escape and nothing after it gets confused.
+    // A ${ ends the token early as an INTERPOLATION, once the matching } is
+    // scanned this is called again for the rest of the string.
+    // Until there is an escape to decode the literal is borrowed from the source.
+    fn string(&mut self) -> Option<Token<'a>> {
+        // Just past the opening " or the } that closed an interpolation
+        let content_start = self.start + 1;
+        let mut decoded: Option<String> = None;
+        let mut bad_escape: Option<Token<'a>> = None;
         while self.peek_next() != '"' && !self.is_at_end() {
-            if self.peek_next() == '\n' {
-                self.line += 1;
+            let content_end = self.current;
+            match self.advance() {
+                '\n' => {
+                    if let Some(decoded) = decoded.as_mut() {
+                        decoded.push('\n');
+                    }
+                    self.new_line();
+                }
+                '$' if self.peek_next() == '{' => {
+                    self.advance();
+                    self.interpolations.push(0);
+                    if let Some(error) = bad_escape {
+                        return Some(error);
+                    }
+                    let literal = self.string_literal(decoded, content_start, content_end);
+                    return Some(self.generate_token(Token_Type::INTERPOLATION, Some(literal)));
+                }
+                '\\' => {
+                    let escape_start = content_end;
+                    let line = self.line;
+                    let column = self.column;
+                    let decoded = decoded.get_or_insert_with(|| self.text(content_start, escape_start).to_string());
+                    match self.escape() {
+                        Ok(c) => decoded.push(c),
+                        Err(message) => {
+                            if bad_escape.is_none() {
+                                let span = Span::new(escape_start, self.current, line, column);
+                                let lexem = self.text(escape_start, self.current);
+                                bad_escape = Some(Token::spanned(Token_Type::ERROR, lexem, message, line, span));
+                            }
+                        }
+                    }
+                }
+                c => {
+                    if let Some(decoded) = decoded.as_mut() {
+                        decoded.push(c);
+                    }
+                }
+            }
+        }
+        let content_end = self.current;
+
+        // The error token covers the rest of the source, that is all string
+        if self.is_at_end() {
+            return Some(self.error_token("Missing closing \" on string token"));
+        }
+
+        self.advance(); // last " is needed
+
+        if let Some(error) = bad_escape {
+            return Some(error);
+        }
+        let literal = self.string_literal(decoded, content_start, content_end);
+        Some(self.generate_token(Token_Type::STRING, Some(literal)))
+    }
+
+    fn string_literal(&self, decoded: Option<String>, start: usize, end: usize) -> Cow<'a, str> {
+        match decoded {
+            Some(decoded) => Cow::Owned(decoded),
+            None => Cow::Borrowed(self.text(start, end)),
+        }
+    }
+
+    // Decodes what follows a backslash, which has already been consumed
+    fn escape(&mut self) -> Result<char, String> {
+        if self.is_at_end() {
+            return Err("Unfinished escape sequence in string".to_string());
+        }
+        let escaped = self.advance();
+        match escaped {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '0' => Ok('\0'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            '\n' => {
+                self.new_line();
+                Err("Unknown escape sequence '\\' followed by a new line in string".to_string())
             }
+            other => Err(format!("Unknown escape sequence '\\{}' in string", other)),
+        }
+    }
+
+    // \u{XXXX} with 1 to 6 hex digits naming a unicode scalar value
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let malformed = "Unicode escape must look like \\u{XXXX} with 1 to 6 hex digits".to_string();
+        if self.peek_next() != '{' {
+            return Err(malformed);
+        }
+        self.advance();
+
+        let digits_start = self.current;
+        while self.peek_next().is_ascii_hexdigit() {
             self.advance();
         }
+        let digits = self.text(digits_start, self.current);
+        // The closing brace belongs to the escape even when the digits are off
+        let closed = self.match_next(&'}');
+        if !closed || digits.is_empty() || digits.len() > 6 {
+            return Err(malformed);
+        }
+
+        let code_point = u32::from_str_radix(digits, 16).unwrap_or_else(|_| panic!("{} is not hex", digits));
+        match char::from_u32(code_point) {
+            Some(c) => Ok(c),
+            None => Err(format!("Unicode escape \\u{{{}}} is not a valid character", digits)),
+        }
+    }
+
+    // Everything up to the next " is taken as it is, backslashes included
+    fn raw_string(&mut self) -> Option<Token<'a>> {
+        while self.peek_next() != '"' && !self.is_at_end() {
+            if self.advance() == '\n' {
+                self.new_line();
+            }
+        }
 
         if self.is_at_end() {
-            self.scanner_error("Missing closing \" on string token");
-            return None;
+            return Some(self.error_token("Missing closing \" on raw string token"));
         }
 
         self.advance(); // last " is needed
 
-        let literal: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        let literal = self.text(self.start + 2, self.current - 1);
 
-        return Some(self.generate_token(Token_Type::STRING, Some(literal)));
+        Some(self.generate_token(Token_Type::STRING, Some(Cow::Borrowed(literal))))
     }
 
-    fn number(&mut self) -> Token {
-        while self.peek_next().is_digit(10) {
+    // 123, 1_000, 2.5, 1e-9, 2.5E+3, 0xFF, 0b1010 and 0o17. The literal is
+    // the value written out plainly, so 0xFF and 255 both give "255".
+    fn number(&mut self, first: char) -> Token<'a> {
+        if first == '0' {
+            let radix = match self.peek_next() {
+                'x' | 'X' => Some((16, "hex")),
+                'b' | 'B' => Some((2, "binary")),
+                'o' | 'O' => Some((8, "octal")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.advance();
+                return self.radix_number(radix, name);
+            }
+        }
+
+        self.decimal_digits();
+        if self.peek_next() == '.' && self.peek_double_next().is_ascii_digit() {
             self.advance();
+            self.decimal_digits();
         }
-        if self.peek_next() == '.' && self.peek_double_next().is_digit(10) {
+        if self.peek_next() == 'e' || self.peek_next() == 'E' {
             self.advance();
-            while self.peek_next().is_digit(10) {
+            if self.peek_next() == '+' || self.peek_next() == '-' {
                 self.advance();
             }
+            if !self.peek_next().is_ascii_digit() {
+                self.number_suffix();
+                return self.error_token("Exponent needs at least one digit");
+            }
+            self.decimal_digits();
+        }
+        // 12abc is a typo, not a number followed by a name
+        let suffix_start = self.current;
+        if self.number_suffix() {
+            let suffix = self.text(suffix_start, self.current);
+            let message = format!("Invalid suffix '{}' on number literal", suffix);
+            return self.error_token(&message);
         }
-        let literal: String = self.source[self.start..self.current].iter().collect();
 
-        return self.generate_token(Token_Type::NUMBER, Some(literal));
+        let lexem = self.text(self.start, self.current);
+        if let Err(message) = check_separators(lexem, 10) {
+            return self.error_token(&message);
+        }
+        // Short plain integers are already written the way they print
+        let is_plain = lexem.len() <= 15
+            && lexem.bytes().all(|b| b.is_ascii_digit())
+            && (lexem == "0" || !lexem.starts_with('0'));
+        if is_plain {
+            return self.generate_token(Token_Type::NUMBER, Some(Cow::Borrowed(lexem)));
+        }
+        match lexem.replace('_', "").parse::<f64>() {
+            Ok(value) => self.generate_token(Token_Type::NUMBER, Some(Cow::Owned(value.to_string()))),
+            Err(_) => self.error_token("Invalid number literal"),
+        }
     }
 
-    fn identifier(&mut self) -> Token {
-        while self.peek_next().is_alphabetic() || self.peek_next() == '_' {
+    // Called once the 0x, 0b or 0o has been consumed
+    fn radix_number(&mut self, radix: u32, name: &str) -> Token<'a> {
+        // Take the whole word so 0xFG is one bad literal rather than 0xF and G
+        while self.peek_next().is_ascii_alphanumeric() || self.peek_next() == '_' {
             self.advance();
         }
+        let lexem = self.text(self.start, self.current);
+        let digits = &lexem[2..];
+        if digits.chars().all(|c| c == '_') {
+            let message = format!("Missing digits after {} in {} literal", &lexem[..2], name);
+            return self.error_token(&message);
+        }
+        if let Some(invalid) = digits.chars().find(|c| *c != '_' && c.to_digit(radix).is_none()) {
+            let message = format!("Invalid digit '{}' in {} literal", invalid, name);
+            return self.error_token(&message);
+        }
+        if let Err(message) = check_separators(digits, radix) {
+            return self.error_token(&message);
+        }
+        // Adding up in a float means huge literals lose precision rather than overflow
+        let value = digits
+            .chars()
+            .filter_map(|c| c.to_digit(radix))
+            .fold(0.0, |value, digit| value * radix as f64 + digit as f64);
+        self.generate_token(Token_Type::NUMBER, Some(Cow::Owned(value.to_string())))
+    }
 
-        let identifier: String = self.source[self.start..self.current].iter().collect();
-        let token_type: Token_Type = match identifier.trim() {
+    fn decimal_digits(&mut self) {
+        while self.peek_next().is_ascii_digit() || self.peek_next() == '_' {
+            self.advance();
+        }
+    }
+
+    // Consumes any letters stuck to the end of a number, true if there were some
+    fn number_suffix(&mut self) -> bool {
+        let mut found = false;
+        while is_xid_continue(self.peek_next()) {
+            self.advance();
+            found = true;
+        }
+        found
+    }
+
+    fn identifier(&mut self) -> Token<'a> {
+        while is_xid_continue(self.peek_next()) {
+            self.advance();
+        }
+
+        // Both ways of writing é should name the same variable, so the lexem
+        // is normalized and everything after the scanner compares that
+        let written = self.text(self.start, self.current);
+        let identifier: Cow<'a, str> = match is_nfc(written) {
+            true => Cow::Borrowed(written),
+            false => Cow::Owned(written.nfc().collect()),
+        };
+        self.check_confusable(written, &identifier);
+        let token_type: Token_Type = match identifier.as_ref() {
             "and" => Token_Type::AND,
             "class" => Token_Type::CLASS,
             "else" => Token_Type::ELSE,
@@ -210,29 +561,91 @@ impl Scanner {
             "while" => Token_Type::WHILE,
             _ => Token_Type::IDENTIFIER,
         };
-        return self.generate_token(token_type, None);
+        let mut token = self.generate_token(token_type, None);
+        token.lexem = identifier;
+        token
+    }
+
+    // Warns about names like pаypal, where the а is Cyrillic, that look like
+    // something else. Only non-ASCII lookalikes count so plain mixes like
+    // 变量x stay quiet.
+    fn check_confusable(&mut self, written: &str, identifier: &str) {
+        if identifier.is_ascii() || identifier.is_single_script() {
+            return;
+        }
+        let mut lookalikes: Vec<char> = Vec::new();
+        for c in identifier.chars() {
+            if !c.is_ascii() && is_potential_mixed_script_confusable_char(c) && !lookalikes.contains(&c) {
+                lookalikes.push(c);
+            }
+        }
+        if lookalikes.is_empty() {
+            return;
+        }
+        let looks_like: String = identifier
+            .chars()
+            .map(|c| match lookalikes.contains(&c) {
+                true => skeleton(&c.to_string()).collect(),
+                false => c.to_string(),
+            })
+            .collect();
+        let mut warning = Diagnostic::new(
+            Severity::Warning,
+            "W0001",
+            &format!("Identifier mixes scripts and could be mistaken for '{}'", looks_like),
+            self.span(),
+        );
+        warning.lexeme = Some(written.to_string());
+        for c in lookalikes {
+            let note = format!(
+                "'{}' (U+{:04X}) looks like '{}'",
+                c,
+                c as u32,
+                skeleton(&c.to_string()).collect::<String>()
+            );
+            warning = warning.with_note(&note);
+        }
+        self.warnings.push(warning);
     }
 
     fn is_at_end(&mut self) -> bool {
         self.current >= self.source.len()
     }
 
-    fn scanner_error(&mut self, message: &str) {
-        Lox::error(self.line, message);
-        self.an_error_occured = true;
+    fn error_token(&mut self, message: &str) -> Token<'a> {
+        self.generate_token(Token_Type::ERROR, Some(Cow::Owned(message.to_string())))
     }
 }
 
+// A _ is only allowed between two digits, so 1_000 but not 1__000 or 1_
+fn check_separators(text: &str, radix: u32) -> Result<(), String> {
+    let chars: Vec<char> = text.chars().collect();
+    for (index, c) in chars.iter().enumerate() {
+        if *c != '_' {
+            continue;
+        }
+        let after_digit = index > 0 && chars[index - 1].is_digit(radix);
+        let before_digit = index + 1 < chars.len() && chars[index + 1].is_digit(radix);
+        if !after_digit || !before_digit {
+            return Err("Digit separator _ must be between two digits".to_string());
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::Cow;
     use super::Scanner;
+    use super::Span;
     use super::Token;
+    use super::Token_Type;
 
     fn token_to_readable(token: &Token) -> String {
-        return token.test_string();
+        token.test_string()
     }
 
-    fn compare_token_with_expected(actual: &Vec<Token>, expected: &Vec<&str>) {
+    fn compare_token_with_expected(actual: &[Token], expected: &[&str]) {
         if actual.len() != expected.len() {
             panic!("Expected and actual not the same length");
         }
@@ -243,12 +656,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scanner_iterator() {
+        let mut scanner = Scanner::new("var x = \"hi\" + \"a\\tb\";");
+        let first = scanner.next().unwrap();
+        assert_eq!(first.token_type, Token_Type::VAR);
+        // Lexems and plain literals point into the source, only decoding copies
+        let rest: Vec<Token> = scanner.by_ref().collect();
+        assert!(rest.iter().all(|token| matches!(token.lexem, Cow::Borrowed(_))));
+        assert!(matches!(rest[2].literal, Cow::Borrowed("hi")));
+        assert!(matches!(rest[4].literal, Cow::Owned(_)));
+        assert_eq!(rest.last().unwrap().token_type, Token_Type::EOF);
+        // Nothing comes after the EOF
+        assert!(scanner.next().is_none());
+    }
+
     #[test]
     fn test_scanner_simple() {
         let code = "(  ".to_string();
         let mut scanner = Scanner::new(&code);
         let expected = vec!["'(' '(' '' '1'", "'EOF' '' '' '1'"];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
 
@@ -274,7 +702,7 @@ mod tests {
             "';' ';' '' '4'",
             "'EOF' '' '' '5'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
 
@@ -300,7 +728,7 @@ mod tests {
             "'>=' '>=' '' '5'",
             "'EOF' '' '' '6'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
 
@@ -319,10 +747,44 @@ mod tests {
             "'/' '/' '' '4'",
             "'EOF' '' '' '5'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
 
+    #[test]
+    fn test_scanner_block_comments() {
+        let code = "( /* one\n /* nested\n */ still\n in */ )\n/**/ / /*/ */ *";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'(' '(' '' '1'",
+            "')' ')' '' '4'",
+            "'/' '/' '' '5'",
+            "'*' '*' '' '5'",
+            "'EOF' '' '' '5'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+
+        let mut scanner = Scanner::new("x /* a /* b */\n");
+        let expected = vec![
+            "'IDENTIFIER' 'x' '' '1'",
+            "'ERROR' '/* a /* b */\n' 'Missing closing */ on block comment' '2'",
+            "'EOF' '' '' '2'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
+
+    #[test]
+    fn test_scanner_doc_comments() {
+        let code = "/// First line\r\n///Second\n// not docs\n//// not docs either\nfun f\n/// Trailing";
+        let mut scanner = Scanner::new(code);
+        let tokens = scanner.scan_tokens();
+        assert_eq!(tokens[0].doc.as_deref(), Some("First line\nSecond"));
+        assert_eq!(tokens[1].doc, None);
+        // Nothing follows, so it ends up on the EOF
+        assert_eq!(tokens[2].token_type, Token_Type::EOF);
+        assert_eq!(tokens[2].doc.as_deref(), Some("Trailing"));
+    }
+
     #[test]
     fn test_scanner_strings() {
         let code = "*
@@ -339,7 +801,7 @@ x\""
             "'STRING' '\"xx \nx\"' 'xx \nx' '4'",
             "'EOF' '' '' '4'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
 
         compare_token_with_expected(&actual, &expected);
     }
@@ -361,10 +823,57 @@ x\""
             "'NUMBER' '2323.2323' '2323.2323' '2'",
             "'EOF' '' '' '3'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
 
+    #[test]
+    fn test_scanner_number_forms() {
+        let code = "0xFF 0Xff 0b1010 0o17 1e-9 2.5E+3 1e3 1_000_000 0x_dead_BEEF 007 2.x";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'NUMBER' '0xFF' '255' '1'",
+            "'NUMBER' '0Xff' '255' '1'",
+            "'NUMBER' '0b1010' '10' '1'",
+            "'NUMBER' '0o17' '15' '1'",
+            "'NUMBER' '1e-9' '0.000000001' '1'",
+            "'NUMBER' '2.5E+3' '2500' '1'",
+            "'NUMBER' '1e3' '1000' '1'",
+            "'NUMBER' '1_000_000' '1000000' '1'",
+            "'ERROR' '0x_dead_BEEF' 'Digit separator _ must be between two digits' '1'",
+            "'NUMBER' '007' '7' '1'",
+            "'NUMBER' '2' '2' '1'",
+            "'.' '.' '' '1'",
+            "'IDENTIFIER' 'x' '' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
+
+    #[test]
+    fn test_scanner_number_errors() {
+        let code = "0x 0b_ 0o8 0b102 0xFG 1e 1e+ 2.5Ex 1__0 1_ 1_e5 12abc 1.5e3x;";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'ERROR' '0x' 'Missing digits after 0x in hex literal' '1'",
+            "'ERROR' '0b_' 'Missing digits after 0b in binary literal' '1'",
+            "'ERROR' '0o8' 'Invalid digit '8' in octal literal' '1'",
+            "'ERROR' '0b102' 'Invalid digit '2' in binary literal' '1'",
+            "'ERROR' '0xFG' 'Invalid digit 'G' in hex literal' '1'",
+            "'ERROR' '1e' 'Exponent needs at least one digit' '1'",
+            "'ERROR' '1e+' 'Exponent needs at least one digit' '1'",
+            "'ERROR' '2.5Ex' 'Exponent needs at least one digit' '1'",
+            "'ERROR' '1__0' 'Digit separator _ must be between two digits' '1'",
+            "'ERROR' '1_' 'Digit separator _ must be between two digits' '1'",
+            "'ERROR' '1_e5' 'Digit separator _ must be between two digits' '1'",
+            "'ERROR' '12abc' 'Invalid suffix 'abc' on number literal' '1'",
+            "'ERROR' '1.5e3x' 'Invalid suffix 'x' on number literal' '1'",
+            "';' ';' '' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
+
     #[test]
     fn test_scanner_identifier_keywords() {
         let code = "*
@@ -404,7 +913,198 @@ x\""
             "'IDENTIFIER' 'hello_world' '' '9'",
             "'EOF' '' '' '10'",
         ];
-        let actual = scanner.scan_tokens().ok().unwrap();
+        let actual = scanner.scan_tokens();
         compare_token_with_expected(&actual, &expected);
     }
+
+    #[test]
+    fn test_scanner_unicode_identifiers() {
+        let code = "x1 _9 café 变量 ℘ Δx \u{301}a 1x";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'IDENTIFIER' 'x1' '' '1'",
+            "'IDENTIFIER' '_9' '' '1'",
+            "'IDENTIFIER' 'café' '' '1'",
+            "'IDENTIFIER' '变量' '' '1'",
+            "'IDENTIFIER' '℘' '' '1'",
+            "'IDENTIFIER' 'Δx' '' '1'",
+            // A combining mark can continue a name but not start one
+            "'ERROR' '\u{301}' 'Unknown char \u{301} unable to be scanned' '1'",
+            "'IDENTIFIER' 'a' '' '1'",
+            "'ERROR' '1x' 'Invalid suffix 'x' on number literal' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+        assert!(scanner.warnings().is_empty());
+
+        // e followed by a combining acute is the same name as é
+        let mut scanner = Scanner::new("cafe\u{301} == café");
+        let tokens = scanner.scan_tokens();
+        assert_eq!(tokens[0].lexem, tokens[2].lexem);
+        assert_eq!(tokens[0].span, Span::new(0, 6, 1, 1));
+    }
+
+    #[test]
+    fn test_scanner_confusable_identifiers() {
+        // The second a is Cyrillic
+        let mut scanner = Scanner::new("var pаypаl;\nvar ѕcope;\nvar 变量x;");
+        scanner.scan_tokens();
+        let warnings: Vec<String> = scanner
+            .warnings()
+            .iter()
+            .map(|warning| format!("{} {}: {} {:?}", warning.span.line, warning.code, warning.message, warning.notes))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "1 W0001: Identifier mixes scripts and could be mistaken for 'paypal' [\"'а' (U+0430) looks like 'a'\"]",
+                "2 W0001: Identifier mixes scripts and could be mistaken for 'scope' [\"'ѕ' (U+0455) looks like 's'\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_errors() {
+        let code = "( @
+            \"never closed"
+        .to_string();
+        let mut scanner = Scanner::new(&code);
+        let expected = vec![
+            "'(' '(' '' '1'",
+            "'ERROR' '@' 'Unknown char @ unable to be scanned' '1'",
+            "'ERROR' '\"never closed' 'Missing closing \" on string token' '2'",
+            "'EOF' '' '' '2'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
+
+    #[test]
+    fn test_scanner_spans() {
+        let code = "var a\r\n\tprint \"ü\" ;\n\"a\nb\" x";
+        let mut scanner = Scanner::new(code);
+        let spans: Vec<Span> = scanner
+            .scan_tokens()
+            .iter()
+            .map(|token| token.span)
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 3, 1, 1),
+                Span::new(4, 5, 1, 5),
+                // A tab is one column
+                Span::new(8, 13, 2, 2),
+                // Offsets are in bytes, columns in chars
+                Span::new(14, 18, 2, 8),
+                Span::new(19, 20, 2, 12),
+                // Multi-line tokens start where they start
+                Span::new(21, 26, 3, 1),
+                Span::new(27, 28, 4, 4),
+                Span::new(28, 28, 4, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_escapes() {
+        let code = r#""a\nb" "\t\r\0" "say \"hi\"" "back\\slash" "\u{48}\u{e9}\u{1F600}""#;
+        let mut scanner = Scanner::new(code);
+        let literals: Vec<String> = scanner
+            .scan_tokens()
+            .iter()
+            .map(|token| token.literal.to_string())
+            .collect();
+        assert_eq!(
+            literals,
+            vec!["a\nb", "\t\r\0", "say \"hi\"", "back\\slash", "Hé😀", ""]
+        );
+    }
+
+    #[test]
+    fn test_scanner_escape_errors() {
+        let code = "print \"ok\\q\\z\";\n  \"\\u{110000}\" \"\\u{}\" \"\\u41\" \"\\u{1234567}\"";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'PRINT' 'print' '' '1'",
+            // Only the first bad escape in a string is reported
+            "'ERROR' '\\q' 'Unknown escape sequence '\\q' in string' '1'",
+            "';' ';' '' '1'",
+            "'ERROR' '\\u{110000}' 'Unicode escape \\u{110000} is not a valid character' '2'",
+            "'ERROR' '\\u{}' 'Unicode escape must look like \\u{XXXX} with 1 to 6 hex digits' '2'",
+            "'ERROR' '\\u' 'Unicode escape must look like \\u{XXXX} with 1 to 6 hex digits' '2'",
+            "'ERROR' '\\u{1234567}' 'Unicode escape must look like \\u{XXXX} with 1 to 6 hex digits' '2'",
+            "'EOF' '' '' '2'",
+        ];
+        let tokens = scanner.scan_tokens();
+        compare_token_with_expected(&tokens, &expected);
+        // The error points at the escape itself
+        assert_eq!(tokens[1].span, Span::new(9, 11, 1, 10));
+        assert_eq!(tokens[3].span, Span::new(19, 29, 2, 4));
+    }
+
+    #[test]
+    fn test_scanner_raw_strings() {
+        let code = "r\"C:\\new\\table\" r\"\\d+\\.\\d*\" r\"\" r";
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'STRING' 'r\"C:\\new\\table\"' 'C:\\new\\table' '1'",
+            "'STRING' 'r\"\\d+\\.\\d*\"' '\\d+\\.\\d*' '1'",
+            "'STRING' 'r\"\"' '' '1'",
+            "'IDENTIFIER' 'r' '' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+
+        let mut scanner = Scanner::new("r\"never closed\\");
+        let tokens = scanner.scan_tokens();
+        assert_eq!(tokens[0].literal, "Missing closing \" on raw string token");
+    }
+
+    #[test]
+    fn test_scanner_interpolation() {
+        let code = r#""Hello ${name}, you are ${age + 1}" "${ "in ${x}" }" "\${no}""#;
+        let mut scanner = Scanner::new(code);
+        let expected = vec![
+            "'INTERPOLATION' '\"Hello ${' 'Hello ' '1'",
+            "'IDENTIFIER' 'name' '' '1'",
+            "'INTERPOLATION' '}, you are ${' ', you are ' '1'",
+            "'IDENTIFIER' 'age' '' '1'",
+            "'+' '+' '' '1'",
+            "'NUMBER' '1' '1' '1'",
+            "'STRING' '}\"' '' '1'",
+            // Strings nest inside the embedded expression
+            "'INTERPOLATION' '\"${' '' '1'",
+            "'INTERPOLATION' '\"in ${' 'in ' '1'",
+            "'IDENTIFIER' 'x' '' '1'",
+            "'STRING' '}\"' '' '1'",
+            "'STRING' '}\"' '' '1'",
+            "'STRING' '\"\\${no}\"' '${no}' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
+
+    #[test]
+    fn test_scanner_interpolation_braces() {
+        // Only the } matching the ${ goes back to the string
+        let mut scanner = Scanner::new("\"${ { } }\" }");
+        let expected = vec![
+            "'INTERPOLATION' '\"${' '' '1'",
+            "'{' '{' '' '1'",
+            "'}' '}' '' '1'",
+            "'STRING' '}\"' '' '1'",
+            "'}' '}' '' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+
+        let mut scanner = Scanner::new("\"a ${b");
+        let expected = vec![
+            "'INTERPOLATION' '\"a ${' 'a ' '1'",
+            "'IDENTIFIER' 'b' '' '1'",
+            "'ERROR' '' 'Missing closing } on string interpolation' '1'",
+            "'EOF' '' '' '1'",
+        ];
+        compare_token_with_expected(&scanner.scan_tokens(), &expected);
+    }
 }
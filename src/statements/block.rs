@@ -0,0 +1,18 @@
+use super::statement::{Statement, StatementNode};
+
+#[allow(dead_code)]
+pub struct Block {
+    pub statements: Vec<Box<dyn Statement>>,
+}
+
+impl Statement for Block {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Block(self)
+    }
+}
+#[allow(dead_code)]
+impl Block {
+    pub fn new(statements: Vec<Box<dyn Statement>>) -> Block {
+        Block { statements }
+    }
+}
@@ -0,0 +1,34 @@
+use super::{function::Function, statement::{Statement, StatementNode}};
+use crate::expressions::variable::Variable;
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Class {
+    pub name: Token<'static>,
+    pub superclass: Option<Variable>,
+    pub methods: Vec<Function>,
+    // From the /// comments in front of it
+    pub doc: Option<String>,
+}
+
+impl Statement for Class {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Class(self)
+    }
+}
+#[allow(dead_code)]
+impl Class {
+    pub fn new(
+        name: Token<'static>,
+        superclass: Option<Variable>,
+        methods: Vec<Function>,
+        doc: Option<String>,
+    ) -> Class {
+        Class {
+            name,
+            superclass,
+            methods,
+            doc,
+        }
+    }
+}
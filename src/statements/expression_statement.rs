@@ -0,0 +1,19 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+
+#[allow(dead_code)]
+pub struct ExpressionStatement {
+    pub expression: Box<dyn Expression>,
+}
+
+impl Statement for ExpressionStatement {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Expression(self)
+    }
+}
+#[allow(dead_code)]
+impl ExpressionStatement {
+    pub fn new(expression: Box<dyn Expression>) -> ExpressionStatement {
+        ExpressionStatement { expression }
+    }
+}
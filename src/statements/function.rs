@@ -0,0 +1,36 @@
+use std::rc::Rc;
+
+use super::statement::{Statement, StatementNode};
+use crate::token::token::Token;
+
+// The body is shared with every LoxFunction created from this declaration
+#[allow(dead_code)]
+pub struct Function {
+    pub name: Token<'static>,
+    pub params: Vec<Token<'static>>,
+    pub body: Rc<Vec<Box<dyn Statement>>>,
+    // From the /// comments in front of it
+    pub doc: Option<String>,
+}
+
+impl Statement for Function {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Function(self)
+    }
+}
+#[allow(dead_code)]
+impl Function {
+    pub fn new(
+        name: Token<'static>,
+        params: Vec<Token<'static>>,
+        body: Vec<Box<dyn Statement>>,
+        doc: Option<String>,
+    ) -> Function {
+        Function {
+            name,
+            params,
+            body: Rc::new(body),
+            doc,
+        }
+    }
+}
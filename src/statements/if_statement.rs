@@ -0,0 +1,29 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+
+#[allow(dead_code)]
+pub struct If {
+    pub condition: Box<dyn Expression>,
+    pub then_branch: Box<dyn Statement>,
+    pub else_branch: Option<Box<dyn Statement>>,
+}
+
+impl Statement for If {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::If(self)
+    }
+}
+#[allow(dead_code)]
+impl If {
+    pub fn new(
+        condition: Box<dyn Expression>,
+        then_branch: Box<dyn Statement>,
+        else_branch: Option<Box<dyn Statement>>,
+    ) -> If {
+        If {
+            condition,
+            then_branch,
+            else_branch,
+        }
+    }
+}
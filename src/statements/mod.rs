@@ -0,0 +1,12 @@
+pub mod block;
+pub mod class;
+pub mod expression_statement;
+pub mod function;
+pub mod if_statement;
+pub mod print;
+pub mod return_statement;
+pub mod statement;
+pub mod transformer;
+pub mod var;
+pub mod visitor;
+pub mod while_statement;
@@ -0,0 +1,19 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+
+#[allow(dead_code)]
+pub struct Print {
+    pub expression: Box<dyn Expression>,
+}
+
+impl Statement for Print {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Print(self)
+    }
+}
+#[allow(dead_code)]
+impl Print {
+    pub fn new(expression: Box<dyn Expression>) -> Print {
+        Print { expression }
+    }
+}
@@ -0,0 +1,21 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Return {
+    pub keyword: Token<'static>,
+    pub value: Option<Box<dyn Expression>>,
+}
+
+impl Statement for Return {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Return(self)
+    }
+}
+#[allow(dead_code)]
+impl Return {
+    pub fn new(keyword: Token<'static>, value: Option<Box<dyn Expression>>) -> Return {
+        Return { keyword, value }
+    }
+}
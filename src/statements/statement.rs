@@ -0,0 +1,90 @@
+use super::visitor::Visitor;
+use super::{
+    block::Block, class::Class, expression_statement::ExpressionStatement, function::Function,
+    if_statement::If, print::Print, return_statement::Return, var::Var, while_statement::While,
+};
+use crate::expressions::expression::sealed::Sealed;
+use crate::expressions::expression::{downcast, IntoAny};
+
+// Same as Expression, a statement only says which kind it is
+pub trait Statement: IntoAny + Sealed {
+    fn node(&self) -> StatementNode<'_>;
+}
+
+// Every kind of statement, borrowed
+pub enum StatementNode<'a> {
+    Block(&'a Block),
+    Class(&'a Class),
+    Expression(&'a ExpressionStatement),
+    Function(&'a Function),
+    If(&'a If),
+    Print(&'a Print),
+    Return(&'a Return),
+    Var(&'a Var),
+    While(&'a While),
+}
+
+impl dyn Statement + '_ {
+    pub fn accept<R>(&self, visitor: &dyn Visitor<R>) -> R {
+        match self.node() {
+            StatementNode::Block(element) => visitor.visit_block(element),
+            StatementNode::Class(element) => visitor.visit_class(element),
+            StatementNode::Expression(element) => visitor.visit_expression_statement(element),
+            StatementNode::Function(element) => visitor.visit_function(element),
+            StatementNode::If(element) => visitor.visit_if(element),
+            StatementNode::Print(element) => visitor.visit_print(element),
+            StatementNode::Return(element) => visitor.visit_return(element),
+            StatementNode::Var(element) => visitor.visit_var(element),
+            StatementNode::While(element) => visitor.visit_while(element),
+        }
+    }
+
+    // The /// comment written above a declaration, for documentation tools
+    pub fn doc(&self) -> Option<&str> {
+        match self.node() {
+            StatementNode::Class(element) => element.doc.as_deref(),
+            StatementNode::Function(element) => element.doc.as_deref(),
+            StatementNode::Var(element) => element.doc.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl Sealed for Block {}
+impl Sealed for Class {}
+impl Sealed for ExpressionStatement {}
+impl Sealed for Function {}
+impl Sealed for If {}
+impl Sealed for Print {}
+impl Sealed for Return {}
+impl Sealed for Var {}
+impl Sealed for While {}
+
+// Every kind of statement, owned, for passes that take the tree apart
+pub enum OwnedStatement {
+    Block(Box<Block>),
+    Class(Box<Class>),
+    Expression(Box<ExpressionStatement>),
+    Function(Box<Function>),
+    If(Box<If>),
+    Print(Box<Print>),
+    Return(Box<Return>),
+    Var(Box<Var>),
+    While(Box<While>),
+}
+
+impl dyn Statement {
+    pub fn into_node(self: Box<Self>) -> OwnedStatement {
+        match self.node() {
+            StatementNode::Block(_) => OwnedStatement::Block(downcast(self)),
+            StatementNode::Class(_) => OwnedStatement::Class(downcast(self)),
+            StatementNode::Expression(_) => OwnedStatement::Expression(downcast(self)),
+            StatementNode::Function(_) => OwnedStatement::Function(downcast(self)),
+            StatementNode::If(_) => OwnedStatement::If(downcast(self)),
+            StatementNode::Print(_) => OwnedStatement::Print(downcast(self)),
+            StatementNode::Return(_) => OwnedStatement::Return(downcast(self)),
+            StatementNode::Var(_) => OwnedStatement::Var(downcast(self)),
+            StatementNode::While(_) => OwnedStatement::While(downcast(self)),
+        }
+    }
+}
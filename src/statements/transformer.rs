@@ -0,0 +1,284 @@
+use std::rc::Rc;
+
+use super::statement::{OwnedStatement, Statement};
+use super::{
+    block::Block, class::Class, expression_statement::ExpressionStatement, function::Function,
+    if_statement::If, print::Print, return_statement::Return, var::Var, while_statement::While,
+};
+use crate::expressions::copier::Copier;
+use crate::expressions::expression::OwnedExpression;
+use crate::expressions::transformer::Transformer as ExpressionTransformer;
+use crate::expressions::variable::Variable;
+
+// Same as the expression Transformer, one level up. Expressions inside
+// statements go through the expression methods, so a pass that only rewrites
+// expressions overrides those and gets the statements rebuilt around them
+pub trait Transformer: ExpressionTransformer {
+    fn transform_statement(&mut self, statement: Box<dyn Statement>) -> Box<dyn Statement> {
+        match statement.into_node() {
+            OwnedStatement::Block(element) => self.transform_block(*element),
+            OwnedStatement::Class(element) => self.transform_class(*element),
+            OwnedStatement::Expression(element) => self.transform_expression_statement(*element),
+            OwnedStatement::Function(element) => self.transform_function(*element),
+            OwnedStatement::If(element) => self.transform_if(*element),
+            OwnedStatement::Print(element) => self.transform_print(*element),
+            OwnedStatement::Return(element) => self.transform_return(*element),
+            OwnedStatement::Var(element) => self.transform_var(*element),
+            OwnedStatement::While(element) => self.transform_while(*element),
+        }
+    }
+    fn transform_program(&mut self, statements: Vec<Box<dyn Statement>>) -> Vec<Box<dyn Statement>> {
+        rebuild_statements(self, statements)
+    }
+    fn transform_expression_statement(&mut self, element: ExpressionStatement) -> Box<dyn Statement> {
+        Box::new(rebuild_expression_statement(self, element))
+    }
+    fn transform_print(&mut self, element: Print) -> Box<dyn Statement> {
+        Box::new(rebuild_print(self, element))
+    }
+    fn transform_block(&mut self, element: Block) -> Box<dyn Statement> {
+        Box::new(rebuild_block(self, element))
+    }
+    fn transform_var(&mut self, element: Var) -> Box<dyn Statement> {
+        Box::new(rebuild_var(self, element))
+    }
+    fn transform_if(&mut self, element: If) -> Box<dyn Statement> {
+        Box::new(rebuild_if(self, element))
+    }
+    fn transform_while(&mut self, element: While) -> Box<dyn Statement> {
+        Box::new(rebuild_while(self, element))
+    }
+    fn transform_function(&mut self, element: Function) -> Box<dyn Statement> {
+        Box::new(self.transform_method(element))
+    }
+    // Methods have to stay functions, so they get their own hook
+    fn transform_method(&mut self, element: Function) -> Function {
+        rebuild_function(self, element)
+    }
+    fn transform_return(&mut self, element: Return) -> Box<dyn Statement> {
+        Box::new(rebuild_return(self, element))
+    }
+    fn transform_class(&mut self, element: Class) -> Box<dyn Statement> {
+        Box::new(rebuild_class(self, element))
+    }
+    // A superclass has to stay a variable. It goes through the expression
+    // methods, so renaming variables renames it too, and when a pass turns it
+    // into anything else, e.g. by inlining a constant, the original is kept
+    fn transform_superclass(&mut self, element: Variable) -> Variable {
+        let original = element.clone();
+        match self.transform_expression(Box::new(element)).into_node() {
+            OwnedExpression::Variable(variable) => *variable,
+            _ => original,
+        }
+    }
+}
+
+pub fn rebuild_statements<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    statements: Vec<Box<dyn Statement>>,
+) -> Vec<Box<dyn Statement>> {
+    statements
+        .into_iter()
+        .map(|statement| transformer.transform_statement(statement))
+        .collect()
+}
+
+pub fn rebuild_expression_statement<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    element: ExpressionStatement,
+) -> ExpressionStatement {
+    ExpressionStatement {
+        expression: transformer.transform_expression(element.expression),
+    }
+}
+
+pub fn rebuild_print<T: Transformer + ?Sized>(transformer: &mut T, element: Print) -> Print {
+    Print {
+        expression: transformer.transform_expression(element.expression),
+    }
+}
+
+pub fn rebuild_block<T: Transformer + ?Sized>(transformer: &mut T, element: Block) -> Block {
+    Block {
+        statements: rebuild_statements(transformer, element.statements),
+    }
+}
+
+pub fn rebuild_var<T: Transformer + ?Sized>(transformer: &mut T, element: Var) -> Var {
+    Var {
+        name: element.name,
+        initializer: element
+            .initializer
+            .map(|initializer| transformer.transform_expression(initializer)),
+        doc: element.doc,
+    }
+}
+
+pub fn rebuild_if<T: Transformer + ?Sized>(transformer: &mut T, element: If) -> If {
+    If {
+        condition: transformer.transform_expression(element.condition),
+        then_branch: transformer.transform_statement(element.then_branch),
+        else_branch: element
+            .else_branch
+            .map(|else_branch| transformer.transform_statement(else_branch)),
+    }
+}
+
+pub fn rebuild_while<T: Transformer + ?Sized>(transformer: &mut T, element: While) -> While {
+    While {
+        condition: transformer.transform_expression(element.condition),
+        body: transformer.transform_statement(element.body),
+    }
+}
+
+// Once the tree has run, the body is shared with the LoxFunctions made from
+// it and can't be taken apart, so a copy of it is rebuilt instead
+pub fn rebuild_function<T: Transformer + ?Sized>(transformer: &mut T, element: Function) -> Function {
+    let body = match Rc::try_unwrap(element.body) {
+        Ok(body) => body,
+        Err(body) => Copier.copy_statements(&body),
+    };
+    Function {
+        name: element.name,
+        params: element.params,
+        body: Rc::new(rebuild_statements(transformer, body)),
+        doc: element.doc,
+    }
+}
+
+pub fn rebuild_return<T: Transformer + ?Sized>(transformer: &mut T, element: Return) -> Return {
+    Return {
+        keyword: element.keyword,
+        value: element.value.map(|value| transformer.transform_expression(value)),
+    }
+}
+
+pub fn rebuild_class<T: Transformer + ?Sized>(transformer: &mut T, element: Class) -> Class {
+    Class {
+        name: element.name,
+        superclass: element
+            .superclass
+            .map(|superclass| transformer.transform_superclass(superclass)),
+        methods: element
+            .methods
+            .into_iter()
+            .map(|method| transformer.transform_method(method))
+            .collect(),
+        doc: element.doc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{rebuild_var, Transformer};
+    use crate::expressions::assign::Assign;
+    use crate::expressions::expression::Expression;
+    use crate::expressions::literal::Literal;
+    use crate::expressions::printer::Printer;
+    use crate::expressions::transformer::{rebuild_assign, Transformer as ExpressionTransformer};
+    use crate::expressions::variable::Variable;
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::parser::parser::Parser;
+    use crate::resolver::resolver::Resolver;
+    use crate::scanner::scanner::Scanner;
+    use crate::statements::statement::Statement;
+    use crate::statements::var::Var;
+    use crate::value::value::Value;
+
+    fn parse(code: &str) -> Vec<Box<dyn Statement>> {
+        Parser::new(Scanner::new(code)).parse().ok().unwrap()
+    }
+
+    struct Unchanged;
+    impl ExpressionTransformer for Unchanged {}
+    impl Transformer for Unchanged {}
+
+    // Renames one variable wherever it is declared, read or assigned
+    struct Rename {
+        from: &'static str,
+        to: &'static str,
+    }
+    impl ExpressionTransformer for Rename {
+        fn transform_variable(&mut self, mut element: Variable) -> Box<dyn Expression> {
+            if element.name.lexem == self.from {
+                element.name.lexem = self.to.into();
+            }
+            Box::new(element)
+        }
+        fn transform_assign(&mut self, element: Assign) -> Box<dyn Expression> {
+            let mut element = rebuild_assign(self, element);
+            if element.name.lexem == self.from {
+                element.name.lexem = self.to.into();
+            }
+            Box::new(element)
+        }
+    }
+    impl Transformer for Rename {
+        fn transform_var(&mut self, element: Var) -> Box<dyn Statement> {
+            let mut element = rebuild_var(self, element);
+            if element.name.lexem == self.from {
+                element.name.lexem = self.to.into();
+            }
+            Box::new(element)
+        }
+    }
+
+    #[test]
+    fn test_transformer_rebuilds_program_unchanged() {
+        let code = "class A < B { init(x) { this.x = x; } get() { return super.get() + this.x; } }
+            fun f(n) { if (n < 1) return 0; else { while (n > 0) n = n - 1; } return n; }
+            var a = A(1); print f(a.get()); a;";
+        let before = Printer {}.convert_program(&parse(code));
+        let after = Printer {}.convert_program(&Unchanged.transform_program(parse(code)));
+        assert_eq!(before, after);
+    }
+
+    // Replaces every variable named B with the number 1
+    struct Inline;
+    impl ExpressionTransformer for Inline {
+        fn transform_variable(&mut self, element: Variable) -> Box<dyn Expression> {
+            match element.name.lexem == "B" {
+                true => Box::new(Literal::new(Value::Number(1.0))),
+                false => Box::new(element),
+            }
+        }
+    }
+    impl Transformer for Inline {}
+
+    #[test]
+    fn test_transformer_keeps_superclass() {
+        let program = Inline.transform_program(parse("class A < B { m() { return B; } }"));
+        assert_eq!(Printer {}.convert_program(&program), "(class A < B (fun m () (return 1)))");
+    }
+
+    #[test]
+    fn test_transformer_after_interpreting() {
+        // Running the program leaves the bodies of f and m shared with the
+        // interpreter's functions, so the pass rebuilds copies of them
+        let program = parse("var x = 1; fun f() { x = x + 1; return x; } class C { m() { return x; } } print f();");
+        Resolver::new().resolve(&program).ok().unwrap();
+        let interpreter = Interpreter::with_output(Box::new(io::sink()));
+        interpreter.interpret(&program).ok().unwrap();
+        let program = Rename { from: "x", to: "y" }.transform_program(program);
+        assert_eq!(
+            Printer {}.convert_program(&program),
+            "(var y 1)\n(fun f () (; (= y (+ y 1))) (return y))\n(class C (fun m () (return y)))\n(print (call f))"
+        );
+    }
+
+    #[test]
+    fn test_transformer_rename() {
+        let program = parse("var x = 1; fun f() { x = x + 1; return x; } print f() + y;");
+        let program = Rename { from: "x", to: "y" }.transform_program(program);
+        assert_eq!(
+            Printer {}.convert_program(&program),
+            "(var y 1)\n(fun f () (; (= y (+ y 1))) (return y))\n(print (+ (call f) y))"
+        );
+        // The superclass is a variable like any other
+        let program = parse("class A < B { m() { return B; } }");
+        let program = Rename { from: "B", to: "C" }.transform_program(program);
+        assert_eq!(Printer {}.convert_program(&program), "(class A < C (fun m () (return C)))");
+    }
+}
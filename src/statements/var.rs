@@ -0,0 +1,27 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+use crate::token::token::Token;
+
+#[allow(dead_code)]
+pub struct Var {
+    pub name: Token<'static>,
+    pub initializer: Option<Box<dyn Expression>>,
+    // From the /// comments in front of it
+    pub doc: Option<String>,
+}
+
+impl Statement for Var {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Var(self)
+    }
+}
+#[allow(dead_code)]
+impl Var {
+    pub fn new(name: Token<'static>, initializer: Option<Box<dyn Expression>>, doc: Option<String>) -> Var {
+        Var {
+            name,
+            initializer,
+            doc,
+        }
+    }
+}
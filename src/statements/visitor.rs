@@ -0,0 +1,16 @@
+use super::{
+    block::Block, class::Class, expression_statement::ExpressionStatement, function::Function,
+    if_statement::If, print::Print, return_statement::Return, var::Var, while_statement::While,
+};
+
+pub trait Visitor<ReturnType> {
+    fn visit_expression_statement(&self, element: &ExpressionStatement) -> ReturnType;
+    fn visit_print(&self, element: &Print) -> ReturnType;
+    fn visit_block(&self, element: &Block) -> ReturnType;
+    fn visit_var(&self, element: &Var) -> ReturnType;
+    fn visit_if(&self, element: &If) -> ReturnType;
+    fn visit_while(&self, element: &While) -> ReturnType;
+    fn visit_function(&self, element: &Function) -> ReturnType;
+    fn visit_return(&self, element: &Return) -> ReturnType;
+    fn visit_class(&self, element: &Class) -> ReturnType;
+}
@@ -0,0 +1,21 @@
+use super::statement::{Statement, StatementNode};
+use crate::expressions::expression::Expression;
+
+// Also what for loops are desugared into by the parser
+#[allow(dead_code)]
+pub struct While {
+    pub condition: Box<dyn Expression>,
+    pub body: Box<dyn Statement>,
+}
+
+impl Statement for While {
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::While(self)
+    }
+}
+#[allow(dead_code)]
+impl While {
+    pub fn new(condition: Box<dyn Expression>, body: Box<dyn Statement>) -> While {
+        While { condition, body }
+    }
+}
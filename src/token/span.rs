@@ -0,0 +1,23 @@
+// Where a token sits in the source. start and end are byte offsets, end being
+// exclusive, while line and column are 1 based and point at the first char.
+// Columns count chars, so a tab or a multi-byte char is a single column.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[allow(dead_code)]
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+}
@@ -1,48 +1,87 @@
-use super::token_type::Token_Type;
+use core::fmt;
+use std::borrow::Cow;
+
+use super::{span::Span, token_type::Token_Type};
 
 #[allow(dead_code)]
-#[derive(Clone)]
-pub struct Token {
+#[derive(Clone, Debug)]
+pub struct Token<'a> {
     pub token_type: Token_Type,
-    pub lexem: String,
-    pub literal: String,
+    // Straight out of the scanner both are borrowed from the source, see into_owned
+    pub lexem: Cow<'a, str>,
+    pub literal: Cow<'a, str>,
     pub line: usize,
+    pub span: Span,
+    // The /// comment lines right before the token, without the slashes
+    pub doc: Option<String>,
 }
 
 #[allow(dead_code)]
-impl Token {
-    pub fn new(token_type: Token_Type, lexem: String, literal: String, line: usize) -> Token {
+impl<'a> Token<'a> {
+    // For tokens that don't come out of the scanner and so have no real span
+    pub fn new(
+        token_type: Token_Type,
+        lexem: impl Into<Cow<'a, str>>,
+        literal: impl Into<Cow<'a, str>>,
+        line: usize,
+    ) -> Token<'a> {
+        Token::spanned(token_type, lexem, literal, line, Span::default())
+    }
+
+    pub fn spanned(
+        token_type: Token_Type,
+        lexem: impl Into<Cow<'a, str>>,
+        literal: impl Into<Cow<'a, str>>,
+        line: usize,
+        span: Span,
+    ) -> Token<'a> {
         Token {
             token_type,
-            lexem,
-            literal,
+            lexem: lexem.into(),
+            literal: literal.into(),
             line,
+            span,
+            doc: None,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        return format!(
-            "{} {} {} {}",
-            self.token_type.to_string(),
-            self.lexem,
-            self.literal,
-            self.line
-        );
+    // Copies whatever is borrowed from the source so the token can outlive
+    // it, which is what the tree, errors and runtime values need
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            lexem: Cow::Owned(self.lexem.into_owned()),
+            literal: Cow::Owned(self.literal.into_owned()),
+            line: self.line,
+            span: self.span,
+            doc: self.doc,
+        }
     }
 
     pub fn test_string(&self) -> String {
-        return format!(
+        format!(
             "'{}' '{}' '{}' '{}'",
-            self.token_type.to_string(),
+            self.token_type,
             self.lexem,
             self.literal,
             self.line
-        );
+        )
+    }
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.token_type, self.lexem, self.literal, self.line
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Span;
     use super::Token;
     use super::Token_Type;
 
@@ -50,9 +89,11 @@ mod tests {
     fn test_token_to_string() {
         let token = Token {
             token_type: Token_Type::LEFT_PAREN,
-            lexem: "\"hello world\"".to_string(),
-            literal: "hello world".to_string(),
+            lexem: "\"hello world\"".into(),
+            literal: "hello world".into(),
             line: 18,
+            span: Span::default(),
+            doc: None,
         };
         assert_eq!(token.to_string().trim(), "( \"hello world\" hello world 18")
     }
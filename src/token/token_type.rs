@@ -2,7 +2,7 @@ use core::fmt;
 
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Token_Type {
     // Single-character tokens.
     LEFT_PAREN,
@@ -31,6 +31,8 @@ pub enum Token_Type {
     IDENTIFIER,
     STRING,
     NUMBER,
+    // A piece of string followed by ${, the embedded expression comes next
+    INTERPOLATION,
 
     // Keywords.
     AND,
@@ -50,9 +52,64 @@ pub enum Token_Type {
     VAR,
     WHILE,
 
+    // Something the scanner couldn't make sense of, the literal says why
+    ERROR,
+
     EOF,
 }
 
+#[allow(dead_code)]
+impl Token_Type {
+    // The other way around from {:?}, for reading tokens back from the JSON tree
+    pub fn from_name(name: &str) -> Option<Token_Type> {
+        let token_type = match name {
+            "LEFT_PAREN" => Token_Type::LEFT_PAREN,
+            "RIGHT_PAREN" => Token_Type::RIGHT_PAREN,
+            "LEFT_BRACE" => Token_Type::LEFT_BRACE,
+            "RIGHT_BRACE" => Token_Type::RIGHT_BRACE,
+            "COMMA" => Token_Type::COMMA,
+            "DOT" => Token_Type::DOT,
+            "MINUS" => Token_Type::MINUS,
+            "PLUS" => Token_Type::PLUS,
+            "SEMICOLON" => Token_Type::SEMICOLON,
+            "SLASH" => Token_Type::SLASH,
+            "STAR" => Token_Type::STAR,
+            "BANG" => Token_Type::BANG,
+            "BANG_EQUAL" => Token_Type::BANG_EQUAL,
+            "EQUAL" => Token_Type::EQUAL,
+            "EQUAL_EQUAL" => Token_Type::EQUAL_EQUAL,
+            "GREATER" => Token_Type::GREATER,
+            "GREATER_EQUAL" => Token_Type::GREATER_EQUAL,
+            "LESS" => Token_Type::LESS,
+            "LESS_EQUAL" => Token_Type::LESS_EQUAL,
+            "IDENTIFIER" => Token_Type::IDENTIFIER,
+            "STRING" => Token_Type::STRING,
+            "NUMBER" => Token_Type::NUMBER,
+            "INTERPOLATION" => Token_Type::INTERPOLATION,
+            "AND" => Token_Type::AND,
+            "CLASS" => Token_Type::CLASS,
+            "ELSE" => Token_Type::ELSE,
+            "FALSE" => Token_Type::FALSE,
+            "FUN" => Token_Type::FUN,
+            "FOR" => Token_Type::FOR,
+            "IF" => Token_Type::IF,
+            "NIL" => Token_Type::NIL,
+            "OR" => Token_Type::OR,
+            "PRINT" => Token_Type::PRINT,
+            "RETURN" => Token_Type::RETURN,
+            "SUPER" => Token_Type::SUPER,
+            "THIS" => Token_Type::THIS,
+            "TRUE" => Token_Type::TRUE,
+            "VAR" => Token_Type::VAR,
+            "WHILE" => Token_Type::WHILE,
+            "ERROR" => Token_Type::ERROR,
+            "EOF" => Token_Type::EOF,
+            _ => return None,
+        };
+        Some(token_type)
+    }
+}
+
 impl fmt::Display for Token_Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -79,6 +136,7 @@ impl fmt::Display for Token_Type {
             Token_Type::IDENTIFIER => write!(f, "IDENTIFIER"),
             Token_Type::STRING => write!(f, "STRING"),
             Token_Type::NUMBER => write!(f, "NUMBER"),
+            Token_Type::INTERPOLATION => write!(f, "INTERPOLATION"),
             Token_Type::AND => write!(f, "AND"),
             Token_Type::CLASS => write!(f, "CLASS"),
             Token_Type::ELSE => write!(f, "ELSE"),
@@ -95,6 +153,7 @@ impl fmt::Display for Token_Type {
             Token_Type::TRUE => write!(f, "TRUE"),
             Token_Type::VAR => write!(f, "VAR"),
             Token_Type::WHILE => write!(f, "WHILE"),
+            Token_Type::ERROR => write!(f, "ERROR"),
             Token_Type::EOF => write!(f, "EOF"),
         }
     }
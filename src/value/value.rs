@@ -0,0 +1,111 @@
+use core::fmt;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::interpreter::{callable::LoxCallable, lox_class::LoxClass, lox_instance::LoxInstance};
+
+// Every value a Lox program can produce. Literals in the AST carry one of these
+// directly and the interpreter evaluates every expression down to one.
+// Everything beyond the primitives is reference counted so passing values around
+// stays cheap, and instances are shared so a change through one reference is
+// seen through all of them.
+#[derive(Clone, Debug)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+}
+
+#[allow(dead_code)]
+impl Value {
+    pub fn string(value: &str) -> Value {
+        Value::String(Rc::from(value))
+    }
+
+    // nil and false are falsey, everything else is truthy
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Nil => false,
+            Value::Bool(value) => *value,
+            _ => true,
+        }
+    }
+
+    // Used in runtime error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Callable(_) => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+        }
+    }
+}
+
+// Values of different types are never equal, and callables, classes and
+// instances are only equal to themselves
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Callable(left), Value::Callable(right)) => Rc::ptr_eq(left, right),
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Callable(callable) => write!(f, "{}", callable),
+            Value::Class(class) => write!(f, "{}", class),
+            Value::Instance(instance) => write!(f, "{}", instance.borrow()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn test_value_to_string() {
+        assert_eq!(Value::Nil.to_string(), "nil");
+        assert_eq!(Value::Bool(true).to_string(), "true");
+        assert_eq!(Value::Number(3.0).to_string(), "3");
+        assert_eq!(Value::Number(2.5).to_string(), "2.5");
+        assert_eq!(Value::string("hi").to_string(), "hi");
+    }
+
+    #[test]
+    fn test_value_truthiness() {
+        assert!(!Value::Nil.is_truthy());
+        assert!(!Value::Bool(false).is_truthy());
+        assert!(Value::Bool(true).is_truthy());
+        assert!(Value::Number(0.0).is_truthy());
+        assert!(Value::string("").is_truthy());
+    }
+
+    #[test]
+    fn test_value_equality() {
+        assert_eq!(Value::Nil, Value::Nil);
+        assert_eq!(Value::string("a"), Value::string("a"));
+        assert_ne!(Value::Number(1.0), Value::string("1"));
+        assert_ne!(Value::Nil, Value::Bool(false));
+    }
+}